@@ -0,0 +1,388 @@
+use std::convert::TryInto;
+
+use gltf::accessor::{
+    sparse::{IndexType, Sparse},
+    DataType, Dimensions,
+};
+
+use crate::document::Document;
+
+/// The decoded components of an accessor, stored element by element.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessorData {
+    /// Number of components per element, e.g. 3 for `VEC3`.
+    pub components: usize,
+    pub values: Vec<f64>,
+}
+
+impl AccessorData {
+    pub fn elements(&self) -> impl Iterator<Item = &[f64]> {
+        self.values.chunks(self.components.max(1))
+    }
+
+    /// Smallest and largest value of each component, or `None` if there are no elements.
+    pub fn bounds(&self) -> Option<(Vec<f64>, Vec<f64>)> {
+        let mut elements = self.elements();
+        let first = elements.next()?;
+        let mut min = first.to_vec();
+        let mut max = first.to_vec();
+        for element in elements {
+            for (i, &value) in element.iter().enumerate() {
+                min[i] = min[i].min(value);
+                max[i] = max[i].max(value);
+            }
+        }
+        Some((min, max))
+    }
+}
+
+/// Reads the values of an accessor like a renderer would, converting normalized integers to
+/// floating point values.
+pub fn read_normalized(accessor: &gltf::Accessor<'_>, document: &Document) -> Option<AccessorData> {
+    let mut data = read(accessor, document)?;
+    if accessor.normalized() {
+        let data_type = accessor.data_type();
+        for value in &mut data.values {
+            *value = normalize(*value, data_type);
+        }
+    }
+    Some(data)
+}
+
+/// Reads the raw component values of an accessor, applying byte stride and sparse substitution.
+///
+/// Returns `None` if any of the referenced data lies outside of its buffer view or buffer, if
+/// a buffer could not be loaded, or if there is not enough memory for `count` elements.
+pub fn read(accessor: &gltf::Accessor<'_>, document: &Document) -> Option<AccessorData> {
+    let data_type = accessor.data_type();
+    let dimensions = accessor.dimensions();
+    let components = dimensions.multiplicity();
+    let count = accessor.count();
+    let element_size = element_size(dimensions, data_type);
+    let len = count.checked_mul(components)?;
+
+    let mut values = match accessor.view() {
+        Some(view) => {
+            let bytes = view_bytes(&view, document)?;
+            let stride = view.stride().unwrap_or(element_size);
+            // The count comes from the JSON, so it is checked against the data before
+            // anything is allocated for it.
+            if count > 0 {
+                let end = stride
+                    .checked_mul(count - 1)?
+                    .checked_add(accessor.offset())?
+                    .checked_add(element_size)?;
+                if end > bytes.len() {
+                    return None;
+                }
+            }
+            let mut values = Vec::with_capacity(len);
+            for element in 0..count {
+                let start = accessor.offset() + element * stride;
+                for component in 0..components {
+                    let offset = start + component_offset(dimensions, data_type, component);
+                    values.push(read_component(bytes, offset, data_type)?);
+                }
+            }
+            values
+        }
+        // Without a buffer view there is no data to check the count against.
+        None => {
+            let mut values = Vec::new();
+            values.try_reserve_exact(len).ok()?;
+            values.resize(len, 0.0);
+            values
+        }
+    };
+
+    if let Some(sparse) = accessor.sparse() {
+        let indices = sparse_indices(&sparse, document)?;
+        let values_bytes = view_bytes(&sparse.values().view(), document)?;
+
+        for (i, index) in indices.into_iter().enumerate() {
+            if index >= count {
+                return None;
+            }
+            for component in 0..components {
+                let offset = sparse.values().offset() as usize
+                    + i * element_size
+                    + component_offset(dimensions, data_type, component);
+                values[index * components + component] =
+                    read_component(values_bytes, offset, data_type)?;
+            }
+        }
+    }
+
+    Some(AccessorData { components, values })
+}
+
+/// The element indices that sparse values replace, or `None` if they lie outside of their
+/// buffer view or its buffer could not be loaded.
+pub fn sparse_indices(sparse: &Sparse<'_>, document: &Document) -> Option<Vec<usize>> {
+    let indices = sparse.indices();
+    let bytes = view_bytes(&indices.view(), document)?;
+    let data_type = match indices.index_type() {
+        IndexType::U8 => DataType::U8,
+        IndexType::U16 => DataType::U16,
+        IndexType::U32 => DataType::U32,
+    };
+
+    (0..sparse.count() as usize)
+        .map(|i| {
+            let offset =
+                (indices.offset() as usize).checked_add(i.checked_mul(data_type.size())?)?;
+            read_component(bytes, offset, data_type).map(|index| index as usize)
+        })
+        .collect()
+}
+
+/// Size of a single element in bytes, including the padding that aligns matrix columns to
+/// four bytes.
+pub fn element_size(dimensions: Dimensions, data_type: DataType) -> usize {
+    match matrix_rows(dimensions) {
+        Some(rows) => rows * column_stride(rows, data_type),
+        None => dimensions.multiplicity() * data_type.size(),
+    }
+}
+
+/// Names for the components of an element, e.g. `x, y, z`, or `m<row><column>` for matrices,
+/// which are stored column by column.
+pub fn component_names(dimensions: Dimensions) -> Vec<String> {
+    match dimensions {
+        Dimensions::Scalar => vec![String::from("value")],
+        Dimensions::Vec2 | Dimensions::Vec3 | Dimensions::Vec4 => ["x", "y", "z", "w"]
+            [..dimensions.multiplicity()]
+            .iter()
+            .map(|name| String::from(*name))
+            .collect(),
+        Dimensions::Mat2 | Dimensions::Mat3 | Dimensions::Mat4 => {
+            let rows = matrix_rows(dimensions).unwrap_or(0);
+            (0..rows * rows)
+                .map(|i| format!("m{}{}", i % rows, i / rows))
+                .collect()
+        }
+    }
+}
+
+fn matrix_rows(dimensions: Dimensions) -> Option<usize> {
+    match dimensions {
+        Dimensions::Mat2 => Some(2),
+        Dimensions::Mat3 => Some(3),
+        Dimensions::Mat4 => Some(4),
+        _ => None,
+    }
+}
+
+fn column_stride(rows: usize, data_type: DataType) -> usize {
+    (rows * data_type.size() + 3) / 4 * 4
+}
+
+fn component_offset(dimensions: Dimensions, data_type: DataType, component: usize) -> usize {
+    match matrix_rows(dimensions) {
+        Some(rows) => {
+            (component / rows) * column_stride(rows, data_type)
+                + (component % rows) * data_type.size()
+        }
+        None => component * data_type.size(),
+    }
+}
+
+fn normalize(value: f64, data_type: DataType) -> f64 {
+    match data_type {
+        DataType::I8 => (value / 127.0).max(-1.0),
+        DataType::U8 => value / 255.0,
+        DataType::I16 => (value / 32767.0).max(-1.0),
+        DataType::U16 => value / 65535.0,
+        DataType::U32 | DataType::F32 => value,
+    }
+}
+
+/// The bytes of a buffer view, or `None` if its buffer can't be loaded or the view doesn't
+/// fit inside it.
+pub fn view_bytes<'a>(view: &gltf::buffer::View<'_>, document: &'a Document) -> Option<&'a [u8]> {
+    let buffer = document.buffer(view.buffer().index()).ok()?;
+    buffer.get(view.offset()..view.offset().checked_add(view.length())?)
+}
+
+fn read_component(bytes: &[u8], offset: usize, data_type: DataType) -> Option<f64> {
+    let bytes = bytes.get(offset..offset.checked_add(data_type.size())?)?;
+    Some(match data_type {
+        DataType::I8 => f64::from(bytes[0] as i8),
+        DataType::U8 => f64::from(bytes[0]),
+        DataType::I16 => f64::from(i16::from_le_bytes(bytes.try_into().ok()?)),
+        DataType::U16 => f64::from(u16::from_le_bytes(bytes.try_into().ok()?)),
+        DataType::U32 => f64::from(u32::from_le_bytes(bytes.try_into().ok()?)),
+        DataType::F32 => f64::from(f32::from_le_bytes(bytes.try_into().ok()?)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    /// A document with one buffer holding `bytes` and the given buffer views and accessors.
+    fn document(bytes: &[u8], views: Value, accessors: Value) -> Arc<Document> {
+        let json = json!({
+            "asset": { "version": "2.0" },
+            "buffers": [{
+                "byteLength": bytes.len(),
+                "uri": format!("data:application/octet-stream;base64,{}", base64::encode(bytes)),
+            }],
+            "bufferViews": views,
+            "accessors": accessors,
+        });
+        Document::import_slice(json.to_string().as_bytes(), None).unwrap()
+    }
+
+    fn read_first(document: &Document, normalized: bool) -> Option<Vec<f64>> {
+        let accessor = document.document.accessors().next().unwrap();
+        let data = if normalized {
+            read_normalized(&accessor, document)
+        } else {
+            read(&accessor, document)
+        };
+        data.map(|data| data.values)
+    }
+
+    #[test]
+    fn normalized_unsigned() {
+        let document = document(
+            &[0, 51, 255, 0],
+            json!([{ "buffer": 0, "byteLength": 4 }]),
+            json!([{
+                "bufferView": 0,
+                "componentType": 5121,
+                "normalized": true,
+                "count": 2,
+                "type": "VEC2",
+            }]),
+        );
+        assert_eq!(
+            read_first(&document, false),
+            Some(vec![0.0, 51.0, 255.0, 0.0])
+        );
+        assert_eq!(read_first(&document, true), Some(vec![0.0, 0.2, 1.0, 0.0]));
+    }
+
+    #[test]
+    fn normalized_signed_clamps_to_minus_one() {
+        let bytes: Vec<u8> = [-32768i16, -32767, 0, 32767]
+            .iter()
+            .flat_map(|value| value.to_le_bytes().to_vec())
+            .collect();
+        let document = document(
+            &bytes,
+            json!([{ "buffer": 0, "byteLength": 8 }]),
+            json!([{
+                "bufferView": 0,
+                "componentType": 5122,
+                "normalized": true,
+                "count": 4,
+                "type": "SCALAR",
+            }]),
+        );
+        assert_eq!(
+            read_first(&document, true),
+            Some(vec![-1.0, -1.0, 0.0, 1.0])
+        );
+    }
+
+    #[test]
+    fn sparse_substitutes_values() {
+        // Four floats, then the sparse indices 1 and 3, then the sparse values.
+        let mut bytes: Vec<u8> = [1.0f32, 2.0, 3.0, 4.0]
+            .iter()
+            .flat_map(|value| value.to_le_bytes().to_vec())
+            .collect();
+        bytes.extend_from_slice(&[1, 0, 3, 0]);
+        bytes.extend(
+            [20.0f32, 40.0]
+                .iter()
+                .flat_map(|value| value.to_le_bytes().to_vec()),
+        );
+        let document = document(
+            &bytes,
+            json!([
+                { "buffer": 0, "byteLength": 16 },
+                { "buffer": 0, "byteOffset": 16, "byteLength": 4 },
+                { "buffer": 0, "byteOffset": 20, "byteLength": 8 },
+            ]),
+            json!([{
+                "bufferView": 0,
+                "componentType": 5126,
+                "count": 4,
+                "type": "SCALAR",
+                "sparse": {
+                    "count": 2,
+                    "indices": { "bufferView": 1, "componentType": 5123 },
+                    "values": { "bufferView": 2 },
+                },
+            }]),
+        );
+        assert_eq!(
+            read_first(&document, false),
+            Some(vec![1.0, 20.0, 3.0, 40.0])
+        );
+    }
+
+    #[test]
+    fn sparse_without_buffer_view() {
+        let document = document(
+            &[2, 0, 0, 0, 0, 0, 128, 63],
+            json!([
+                { "buffer": 0, "byteLength": 1 },
+                { "buffer": 0, "byteOffset": 4, "byteLength": 4 },
+            ]),
+            json!([{
+                "componentType": 5126,
+                "count": 3,
+                "type": "SCALAR",
+                "sparse": {
+                    "count": 1,
+                    "indices": { "bufferView": 0, "componentType": 5121 },
+                    "values": { "bufferView": 1 },
+                },
+            }]),
+        );
+        assert_eq!(read_first(&document, false), Some(vec![0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn sparse_index_out_of_range() {
+        let document = document(
+            &[5, 0, 0, 0, 0, 0, 128, 63],
+            json!([
+                { "buffer": 0, "byteLength": 1 },
+                { "buffer": 0, "byteOffset": 4, "byteLength": 4 },
+            ]),
+            json!([{
+                "componentType": 5126,
+                "count": 3,
+                "type": "SCALAR",
+                "sparse": {
+                    "count": 1,
+                    "indices": { "bufferView": 0, "componentType": 5121 },
+                    "values": { "bufferView": 1 },
+                },
+            }]),
+        );
+        let accessor = document.document.accessors().next().unwrap();
+        assert_eq!(
+            sparse_indices(&accessor.sparse().unwrap(), &document),
+            Some(vec![5])
+        );
+        assert_eq!(read_first(&document, false), None);
+    }
+
+    #[test]
+    fn matrix_columns_are_padded() {
+        assert_eq!(element_size(Dimensions::Mat2, DataType::U8), 8);
+        assert_eq!(element_size(Dimensions::Mat3, DataType::I16), 24);
+        assert_eq!(element_size(Dimensions::Mat4, DataType::F32), 64);
+        assert_eq!(element_size(Dimensions::Vec3, DataType::U8), 3);
+    }
+}
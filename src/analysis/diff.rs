@@ -0,0 +1,389 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+};
+
+use serde::Serialize;
+
+use super::statistics::Statistics;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Change {
+    pub kind: ChangeKind,
+    /// What kind of object changed, e.g. `node` or `material`.
+    pub category: &'static str,
+    /// Name-based path identifying the object in both documents.
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = match self.kind {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::Modified => '~',
+        };
+        write!(f, "{} {} {}", sign, self.category, self.path)?;
+        if let Some(details) = &self.details {
+            write!(f, ": {}", details)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares two documents, matching scenes, nodes, meshes and materials by name.
+///
+/// Nodes are identified by the names of their scene and ancestors, so re-ordering or
+/// re-indexing objects in an exported file doesn't show up as a change.
+pub fn diff(old: &gltf::Document, new: &gltf::Document) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    diff_counts(&mut changes, old, new);
+    diff_keyed(
+        &mut changes,
+        "node",
+        &node_paths(old),
+        &node_paths(new),
+        diff_nodes,
+    );
+    diff_keyed(
+        &mut changes,
+        "mesh",
+        &keyed(old.meshes(), |mesh| (mesh.name(), mesh.index(), "mesh")),
+        &keyed(new.meshes(), |mesh| (mesh.name(), mesh.index(), "mesh")),
+        diff_meshes,
+    );
+    diff_keyed(
+        &mut changes,
+        "material",
+        &keyed(old.materials(), |material| {
+            (material.name(), material.index().unwrap_or(0), "material")
+        }),
+        &keyed(new.materials(), |material| {
+            (material.name(), material.index().unwrap_or(0), "material")
+        }),
+        diff_materials,
+    );
+
+    changes
+}
+
+fn diff_counts(changes: &mut Vec<Change>, old: &gltf::Document, new: &gltf::Document) {
+    let old = Statistics::from_document(old).rows();
+    let new = Statistics::from_document(new).rows();
+
+    for ((label, old), (_, new)) in old.into_iter().zip(new) {
+        if old != new {
+            changes.push(Change {
+                kind: ChangeKind::Modified,
+                category: "count",
+                path: label.to_owned(),
+                details: Some(format!("{} -> {}", old, new)),
+            });
+        }
+    }
+}
+
+fn diff_keyed<T>(
+    changes: &mut Vec<Change>,
+    category: &'static str,
+    old: &BTreeMap<String, T>,
+    new: &BTreeMap<String, T>,
+    compare: fn(&T, &T) -> Vec<String>,
+) {
+    for (path, old_item) in old {
+        match new.get(path) {
+            Some(new_item) => {
+                let differences = compare(old_item, new_item);
+                if !differences.is_empty() {
+                    changes.push(Change {
+                        kind: ChangeKind::Modified,
+                        category,
+                        path: path.clone(),
+                        details: Some(differences.join(", ")),
+                    });
+                }
+            }
+            None => changes.push(Change {
+                kind: ChangeKind::Removed,
+                category,
+                path: path.clone(),
+                details: None,
+            }),
+        }
+    }
+
+    for path in new.keys().filter(|path| !old.contains_key(*path)) {
+        changes.push(Change {
+            kind: ChangeKind::Added,
+            category,
+            path: path.clone(),
+            details: None,
+        });
+    }
+}
+
+/// Keys items by name, falling back to `<kind index>` for unnamed ones and numbering duplicates.
+fn keyed<'a, T>(
+    items: impl Iterator<Item = T>,
+    key: impl Fn(&T) -> (Option<&'a str>, usize, &'static str),
+) -> BTreeMap<String, T> {
+    let mut seen = HashMap::new();
+    items
+        .map(|item| {
+            let (name, index, kind) = key(&item);
+            (unique_segment(&mut seen, name, index, kind), item)
+        })
+        .collect()
+}
+
+fn unique_segment(
+    seen: &mut HashMap<String, usize>,
+    name: Option<&str>,
+    index: usize,
+    kind: &str,
+) -> String {
+    let segment = match name {
+        Some(name) => name.to_owned(),
+        None => format!("<{} {}>", kind, index),
+    };
+    let occurrence = seen.entry(segment.clone()).or_insert(0);
+    *occurrence += 1;
+    if *occurrence > 1 {
+        format!("{}[{}]", segment, *occurrence - 1)
+    } else {
+        segment
+    }
+}
+
+/// Paths of every node reachable from a scene, in the form `scene/parent/child`.
+pub fn node_paths(document: &gltf::Document) -> BTreeMap<String, gltf::Node<'_>> {
+    fn visit<'a>(
+        paths: &mut BTreeMap<String, gltf::Node<'a>>,
+        parent: &str,
+        nodes: impl Iterator<Item = gltf::Node<'a>>,
+    ) {
+        let mut seen = HashMap::new();
+        for node in nodes {
+            let path = format!(
+                "{}/{}",
+                parent,
+                unique_segment(&mut seen, node.name(), node.index(), "node")
+            );
+            visit(paths, &path, node.children());
+            paths.insert(path, node);
+        }
+    }
+
+    let mut paths = BTreeMap::new();
+    let mut seen = HashMap::new();
+    for scene in document.scenes() {
+        let scene_path = unique_segment(&mut seen, scene.name(), scene.index(), "scene");
+        visit(&mut paths, &scene_path, scene.nodes());
+    }
+    paths
+}
+
+fn diff_nodes(old: &gltf::Node<'_>, new: &gltf::Node<'_>) -> Vec<String> {
+    const EPSILON: f32 = 1e-5;
+
+    let mut differences = Vec::new();
+
+    let (old_translation, old_rotation, old_scale) = old.transform().decomposed();
+    let (new_translation, new_rotation, new_scale) = new.transform().decomposed();
+    if !approx_eq(&old_translation, &new_translation, EPSILON) {
+        differences.push(format!(
+            "translation {:?} -> {:?}",
+            old_translation, new_translation
+        ));
+    }
+    if !approx_eq(&old_rotation, &new_rotation, EPSILON) {
+        differences.push(format!("rotation {:?} -> {:?}", old_rotation, new_rotation));
+    }
+    if !approx_eq(&old_scale, &new_scale, EPSILON) {
+        differences.push(format!("scale {:?} -> {:?}", old_scale, new_scale));
+    }
+
+    let old_mesh = old
+        .mesh()
+        .map(|mesh| describe(mesh.name(), mesh.index(), "mesh"));
+    let new_mesh = new
+        .mesh()
+        .map(|mesh| describe(mesh.name(), mesh.index(), "mesh"));
+    if old_mesh != new_mesh {
+        differences.push(format!(
+            "mesh {} -> {}",
+            or_none(old_mesh),
+            or_none(new_mesh)
+        ));
+    }
+
+    for (kind, old_present, new_present) in [
+        ("camera", old.camera().is_some(), new.camera().is_some()),
+        ("light", old.light().is_some(), new.light().is_some()),
+        ("skin", old.skin().is_some(), new.skin().is_some()),
+    ] {
+        if old_present != new_present {
+            differences.push(if new_present {
+                format!("{} added", kind)
+            } else {
+                format!("{} removed", kind)
+            });
+        }
+    }
+
+    differences
+}
+
+fn diff_meshes(old: &gltf::Mesh<'_>, new: &gltf::Mesh<'_>) -> Vec<String> {
+    let mut differences = Vec::new();
+
+    let old_primitives = old.primitives().collect::<Vec<_>>();
+    let new_primitives = new.primitives().collect::<Vec<_>>();
+    if old_primitives.len() != new_primitives.len() {
+        differences.push(format!(
+            "primitives {} -> {}",
+            old_primitives.len(),
+            new_primitives.len()
+        ));
+    }
+
+    for (index, (old, new)) in old_primitives.iter().zip(&new_primitives).enumerate() {
+        let old_attributes = attribute_names(old);
+        let new_attributes = attribute_names(new);
+        for added in new_attributes.difference(&old_attributes) {
+            differences.push(format!("primitive {} attribute {} added", index, added));
+        }
+        for removed in old_attributes.difference(&new_attributes) {
+            differences.push(format!("primitive {} attribute {} removed", index, removed));
+        }
+
+        if old.mode() != new.mode() {
+            differences.push(format!(
+                "primitive {} mode {:?} -> {:?}",
+                index,
+                old.mode(),
+                new.mode()
+            ));
+        }
+        if old.indices().is_some() != new.indices().is_some() {
+            differences.push(format!(
+                "primitive {} indices {}",
+                index,
+                if new.indices().is_some() {
+                    "added"
+                } else {
+                    "removed"
+                }
+            ));
+        }
+
+        let old_material = describe_material(&old.material());
+        let new_material = describe_material(&new.material());
+        if old_material != new_material {
+            differences.push(format!(
+                "primitive {} material {} -> {}",
+                index, old_material, new_material
+            ));
+        }
+    }
+
+    differences
+}
+
+fn diff_materials(old: &gltf::Material<'_>, new: &gltf::Material<'_>) -> Vec<String> {
+    const EPSILON: f32 = 1e-6;
+
+    let mut differences = Vec::new();
+    let old_pbr = old.pbr_metallic_roughness();
+    let new_pbr = new.pbr_metallic_roughness();
+
+    let factors = [
+        (
+            "baseColorFactor",
+            old_pbr.base_color_factor().to_vec(),
+            new_pbr.base_color_factor().to_vec(),
+        ),
+        (
+            "metallicFactor",
+            vec![old_pbr.metallic_factor()],
+            vec![new_pbr.metallic_factor()],
+        ),
+        (
+            "roughnessFactor",
+            vec![old_pbr.roughness_factor()],
+            vec![new_pbr.roughness_factor()],
+        ),
+        (
+            "emissiveFactor",
+            old.emissive_factor().to_vec(),
+            new.emissive_factor().to_vec(),
+        ),
+        (
+            "alphaCutoff",
+            vec![old.alpha_cutoff()],
+            vec![new.alpha_cutoff()],
+        ),
+    ];
+    for (name, old, new) in &factors {
+        if !approx_eq(old, new, EPSILON) {
+            differences.push(format!("{} {:?} -> {:?}", name, old, new));
+        }
+    }
+
+    if old.alpha_mode() != new.alpha_mode() {
+        differences.push(format!(
+            "alphaMode {:?} -> {:?}",
+            old.alpha_mode(),
+            new.alpha_mode()
+        ));
+    }
+    if old.double_sided() != new.double_sided() {
+        differences.push(format!(
+            "doubleSided {} -> {}",
+            old.double_sided(),
+            new.double_sided()
+        ));
+    }
+
+    differences
+}
+
+fn attribute_names(primitive: &gltf::Primitive<'_>) -> BTreeSet<String> {
+    primitive
+        .attributes()
+        .map(|(semantic, _)| semantic.to_string())
+        .collect()
+}
+
+fn describe(name: Option<&str>, index: usize, kind: &str) -> String {
+    match name {
+        Some(name) => format!("{:?}", name),
+        None => format!("<{} {}>", kind, index),
+    }
+}
+
+fn describe_material(material: &gltf::Material<'_>) -> String {
+    match material.index() {
+        Some(index) => describe(material.name(), index, "material"),
+        None => String::from("<default material>"),
+    }
+}
+
+fn or_none(description: Option<String>) -> String {
+    description.unwrap_or_else(|| String::from("<none>"))
+}
+
+fn approx_eq(a: &[f32], b: &[f32], epsilon: f32) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() <= epsilon)
+}
@@ -0,0 +1,118 @@
+//! Which extensions a document uses, and where.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::document;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Extension {
+    pub name: String,
+    /// Listed in `extensionsUsed`.
+    pub declared: bool,
+    /// Listed in `extensionsRequired`.
+    pub required: bool,
+    /// Understood by the explorer rather than ignored.
+    pub supported: bool,
+    /// JSON pointers to the objects with data for the extension, `""` being the root.
+    pub objects: Vec<String>,
+}
+
+impl Extension {
+    /// Short notes on the status of the extension, like `required, not supported`.
+    pub fn status(&self) -> String {
+        let mut notes = vec![if self.required { "required" } else { "used" }];
+        if !self.declared {
+            notes.push("missing from extensionsUsed");
+        }
+        notes.push(if self.supported {
+            "supported"
+        } else {
+            "not supported"
+        });
+        notes.join(", ")
+    }
+}
+
+/// Like `extensions`, for a document that has been parsed already.
+///
+/// The `gltf` crate drops the data of extensions it doesn't know, so for those only
+/// their listing in `extensionsUsed` and `extensionsRequired` is found.
+pub fn from_document(document: &gltf::Document) -> Vec<Extension> {
+    // Serializing the parsed JSON can only fail for maps with non-string keys, which
+    // glTF doesn't have.
+    let json = serde_json::to_value(document.clone().into_json()).unwrap_or_default();
+    extensions(&json)
+}
+
+/// Every extension declared in the raw JSON of a document or found in one of its
+/// objects, sorted by name.
+///
+/// This works on the raw JSON because the `gltf` crate drops the data of extensions it
+/// doesn't know.
+pub fn extensions(json: &Value) -> Vec<Extension> {
+    let mut objects: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let names = |key: &str| -> Vec<String> {
+        json[key]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect()
+    };
+    let used = names("extensionsUsed");
+    let required = names("extensionsRequired");
+    for name in used.iter().chain(&required) {
+        objects.entry(name.clone()).or_default();
+    }
+
+    visit(json, &mut String::new(), &mut |name, pointer| {
+        objects
+            .entry(name.to_owned())
+            .or_default()
+            .push(pointer.to_owned());
+    });
+
+    objects
+        .into_iter()
+        .map(|(name, objects)| Extension {
+            declared: used.contains(&name),
+            required: required.contains(&name),
+            supported: document::is_supported_extension(&name),
+            objects,
+            name,
+        })
+        .collect()
+}
+
+/// Calls `f` with the name of every extension object below `value`, and the JSON pointer
+/// of the object it extends.
+fn visit(value: &Value, pointer: &mut String, f: &mut impl FnMut(&str, &str)) {
+    let length = pointer.len();
+    match value {
+        Value::Object(object) => {
+            if let Some(extensions) = object.get("extensions").and_then(Value::as_object) {
+                for name in extensions.keys() {
+                    f(name, pointer);
+                }
+            }
+            for (key, value) in object {
+                pointer.push('/');
+                pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                visit(value, pointer, f);
+                pointer.truncate(length);
+            }
+        }
+        Value::Array(array) => {
+            for (index, value) in array.iter().enumerate() {
+                pointer.push_str(&format!("/{}", index));
+                visit(value, pointer, f);
+                pointer.truncate(length);
+            }
+        }
+        _ => {}
+    }
+}
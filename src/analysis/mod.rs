@@ -0,0 +1,7 @@
+pub mod accessor;
+pub mod diff;
+pub mod extensions;
+pub mod query;
+pub mod size;
+pub mod statistics;
+pub mod validation;
@@ -0,0 +1,415 @@
+//! Path expressions over the JSON of a glTF document.
+//!
+//! An expression starts with a top-level property and continues with any number of steps:
+//!
+//! - `.name` selects a property,
+//! - `[3]` selects an array element, counting from the end if negative,
+//! - `[*]` selects every element of an array or every value of an object,
+//! - `[?field]` keeps the array elements where `field` is present and not `null` or
+//!   `false`,
+//! - `[?field == value]`, `[?field != value]` and `[?field ~ value]` compare `field`
+//!   with a JSON literal, where `~` tests whether a string contains a substring or an
+//!   array contains an element.
+//!
+//! Fields in filters may be nested, as in `[?pbrMetallicRoughness.baseColorTexture]`.
+//! Once `[*]` or a filter has been applied the result is an array, from which elements
+//! missing the following steps are dropped.
+
+use std::{error::Error, fmt, str::FromStr};
+
+use serde_json::Value;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Step {
+    Field(String),
+    Index(i64),
+    Wildcard,
+    Filter(Filter),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Filter {
+    field: Vec<String>,
+    condition: Option<(Operator, Value)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Contains,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset into the expression.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for ParseError {}
+
+impl FromStr for Query {
+    type Err = ParseError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        Parser {
+            expression,
+            position: 0,
+        }
+        .query()
+    }
+}
+
+impl Query {
+    /// The JSON a glTF document is made of, which queries are evaluated against.
+    pub fn document_json(document: &gltf::Document) -> Value {
+        serde_json::to_value(document.clone().into_json())
+            .expect("glTF JSON can always be represented as a value")
+    }
+
+    pub fn evaluate(&self, root: &Value) -> Value {
+        let mut current = vec![root];
+        let mut projected = false;
+
+        for step in &self.steps {
+            current = match step {
+                Step::Field(name) => current
+                    .into_iter()
+                    .filter_map(|value| value.get(name))
+                    .collect(),
+                Step::Index(index) => current
+                    .into_iter()
+                    .filter_map(|value| {
+                        let array = value.as_array()?;
+                        let index = if *index < 0 {
+                            array.len().checked_sub(index.unsigned_abs() as usize)?
+                        } else {
+                            *index as usize
+                        };
+                        array.get(index)
+                    })
+                    .collect(),
+                Step::Wildcard => {
+                    projected = true;
+                    current
+                        .into_iter()
+                        .flat_map(|value| -> Box<dyn Iterator<Item = &Value>> {
+                            match value {
+                                Value::Array(array) => Box::new(array.iter()),
+                                Value::Object(object) => Box::new(object.values()),
+                                _ => Box::new(std::iter::empty()),
+                            }
+                        })
+                        .collect()
+                }
+                Step::Filter(filter) => {
+                    projected = true;
+                    current
+                        .into_iter()
+                        .filter_map(Value::as_array)
+                        .flatten()
+                        .filter(|element| filter.matches(element))
+                        .collect()
+                }
+            };
+        }
+
+        if projected {
+            Value::Array(current.into_iter().cloned().collect())
+        } else {
+            current.first().map_or(Value::Null, |&value| value.clone())
+        }
+    }
+}
+
+impl Filter {
+    fn matches(&self, element: &Value) -> bool {
+        let field = self
+            .field
+            .iter()
+            .try_fold(element, |value, name| value.get(name));
+
+        match (&self.condition, field) {
+            (None, field) => !matches!(field, None | Some(Value::Null) | Some(Value::Bool(false))),
+            (Some((Operator::Equal, literal)), field) => field.is_some_and(|v| same(v, literal)),
+            (Some((Operator::NotEqual, literal)), field) => {
+                !field.is_some_and(|v| same(v, literal))
+            }
+            (Some((Operator::Contains, literal)), Some(Value::String(string))) => {
+                literal.as_str().is_some_and(|part| string.contains(part))
+            }
+            (Some((Operator::Contains, literal)), Some(Value::Array(array))) => {
+                array.iter().any(|element| same(element, literal))
+            }
+            (Some((Operator::Contains, _)), _) => false,
+        }
+    }
+}
+
+/// JSON equality, except that numbers are compared by value so that `1` equals `1.0`.
+fn same(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+struct Parser<'a> {
+    expression: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn query(mut self) -> Result<Query, ParseError> {
+        let mut steps = vec![Step::Field(self.identifier()?)];
+
+        self.skip_whitespace();
+        while let Some(c) = self.peek() {
+            match c {
+                '.' => {
+                    self.position += 1;
+                    steps.push(Step::Field(self.identifier()?));
+                }
+                '[' => {
+                    self.position += 1;
+                    steps.push(self.bracket()?);
+                }
+                _ => return Err(self.error(format!("unexpected `{}`", c))),
+            }
+            self.skip_whitespace();
+        }
+
+        Ok(Query { steps })
+    }
+
+    /// Parses what follows a `[`, up to and including the matching `]`.
+    fn bracket(&mut self) -> Result<Step, ParseError> {
+        self.skip_whitespace();
+        let step = match self.peek() {
+            Some('*') => {
+                self.position += 1;
+                Step::Wildcard
+            }
+            Some('?') => {
+                self.position += 1;
+                Step::Filter(self.filter()?)
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.position;
+                self.position += 1;
+                self.take_while(|c| c.is_ascii_digit());
+                let index =
+                    self.expression[start..self.position]
+                        .parse()
+                        .map_err(|_| ParseError {
+                            position: start,
+                            message: String::from("invalid index"),
+                        })?;
+                Step::Index(index)
+            }
+            _ => return Err(self.error("expected an index, `*` or `?`")),
+        };
+        self.expect(']')?;
+        Ok(step)
+    }
+
+    fn filter(&mut self) -> Result<Filter, ParseError> {
+        let mut field = vec![self.identifier()?];
+        while self.peek() == Some('.') {
+            self.position += 1;
+            field.push(self.identifier()?);
+        }
+
+        self.skip_whitespace();
+        let rest = &self.expression[self.position..];
+        let operator = if rest.starts_with("==") {
+            Some((Operator::Equal, 2))
+        } else if rest.starts_with("!=") {
+            Some((Operator::NotEqual, 2))
+        } else if rest.starts_with('~') {
+            Some((Operator::Contains, 1))
+        } else {
+            None
+        };
+
+        let condition = match operator {
+            Some((operator, length)) => {
+                self.position += length;
+                Some((operator, self.literal()?))
+            }
+            None => None,
+        };
+
+        Ok(Filter { field, condition })
+    }
+
+    /// Parses a JSON literal, which ends at the closing `]` unless it is a string.
+    fn literal(&mut self) -> Result<Value, ParseError> {
+        self.skip_whitespace();
+        let start = self.position;
+        let mut stream =
+            serde_json::Deserializer::from_str(&self.expression[start..]).into_iter::<Value>();
+        match stream.next() {
+            Some(Ok(value)) => {
+                self.position = start + stream.byte_offset();
+                Ok(value)
+            }
+            _ => Err(self.error("expected a JSON value")),
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, ParseError> {
+        self.skip_whitespace();
+        let identifier = self.take_while(|c| c.is_alphanumeric() || c == '_');
+        if identifier.is_empty() {
+            Err(self.error("expected a property name"))
+        } else {
+            Ok(identifier.to_owned())
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.position += expected.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{}`", expected)))
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let start = self.position;
+        let rest = &self.expression[start..];
+        let length = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.position += length;
+        &self.expression[start..self.position]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.expression[self.position..].chars().next()
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            position: self.position,
+            message: message.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn evaluate(expression: &str, root: &Value) -> Value {
+        expression.parse::<Query>().unwrap().evaluate(root)
+    }
+
+    fn root() -> Value {
+        json!({
+            "nodes": [
+                { "name": "Root", "children": [1, 2] },
+                { "name": "Tri", "mesh": 0 },
+                { "name": "Cam", "camera": null },
+            ],
+            "materials": [
+                {
+                    "name": "Red",
+                    "doubleSided": true,
+                    "pbrMetallicRoughness": { "baseColorTexture": { "index": 0 } },
+                },
+                { "name": "Plain", "doubleSided": false, "pbrMetallicRoughness": {} },
+            ],
+            "extensions": { "A": { "value": 1 }, "B": { "value": 2 } },
+        })
+    }
+
+    #[test]
+    fn fields_and_indices() {
+        let root = root();
+        assert_eq!(evaluate("nodes[1].name", &root), json!("Tri"));
+        assert_eq!(evaluate("nodes[-1].name", &root), json!("Cam"));
+        assert_eq!(evaluate("nodes[0].children[1]", &root), json!(2));
+        assert_eq!(evaluate("nodes[3]", &root), Value::Null);
+        assert_eq!(evaluate("nodes[-4]", &root), Value::Null);
+        assert_eq!(evaluate("missing.name", &root), Value::Null);
+    }
+
+    #[test]
+    fn wildcards() {
+        let root = root();
+        assert_eq!(
+            evaluate("nodes[*].name", &root),
+            json!(["Root", "Tri", "Cam"])
+        );
+        assert_eq!(evaluate("extensions[*].value", &root), json!([1, 2]));
+        // Elements without a mesh are dropped.
+        assert_eq!(evaluate("nodes[*].mesh", &root), json!([0]));
+    }
+
+    #[test]
+    fn filters() {
+        let root = root();
+        assert_eq!(evaluate("nodes[?mesh].name", &root), json!(["Tri"]));
+        assert_eq!(evaluate("nodes[?camera].name", &root), json!([]));
+        assert_eq!(
+            evaluate("materials[?doubleSided].name", &root),
+            json!(["Red"])
+        );
+        assert_eq!(
+            evaluate(
+                "materials[?pbrMetallicRoughness.baseColorTexture].name",
+                &root
+            ),
+            json!(["Red"])
+        );
+    }
+
+    #[test]
+    fn comparisons() {
+        let root = root();
+        assert_eq!(evaluate("nodes[?name == \"Tri\"].mesh", &root), json!([0]));
+        assert_eq!(evaluate("nodes[?mesh == 0.0].name", &root), json!(["Tri"]));
+        assert_eq!(
+            evaluate("nodes[?mesh != 0].name", &root),
+            json!(["Root", "Cam"])
+        );
+        assert_eq!(evaluate("nodes[?name ~ \"r\"].name", &root), json!(["Tri"]));
+        assert_eq!(
+            evaluate("nodes[?children ~ 2].name", &root),
+            json!(["Root"])
+        );
+        assert_eq!(evaluate("nodes[?mesh ~ 0].name", &root), json!([]));
+        // A string literal may contain the closing bracket.
+        assert_eq!(evaluate("nodes[?name == \"]\"]", &root), json!([]));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |expression: &str| expression.parse::<Query>().unwrap_err();
+        assert_eq!(error("").position, 0);
+        assert_eq!(error("nodes[").position, 6);
+        assert_eq!(error("nodes[0").message, "expected `]`");
+        assert_eq!(error("nodes[?name == ]").message, "expected a JSON value");
+        assert_eq!(error("nodes/name").message, "unexpected `/`");
+    }
+}
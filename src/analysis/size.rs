@@ -0,0 +1,263 @@
+//! Where the bytes of a document go.
+
+use std::{collections::BTreeSet, io::Cursor};
+
+use serde::Serialize;
+
+use crate::document::Document;
+
+/// The bytes attributed to a single object.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    pub index: usize,
+    pub name: Option<String>,
+    /// Buffer bytes, or encoded image bytes for images, textures and materials.
+    pub bytes: usize,
+    /// The size of the images as decoded RGBA, for images, textures and materials.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoded_bytes: Option<usize>,
+}
+
+impl Entry {
+    /// The index and name of the object, like `3 "Body"`.
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} {:?}", self.index, name),
+            None => self.index.to_string(),
+        }
+    }
+
+    /// The formatted size, like `1.5 MiB (12.0 MiB decoded)`.
+    pub fn size(&self) -> String {
+        match self.decoded_bytes {
+            Some(decoded) => format!(
+                "{} ({} decoded)",
+                format_bytes(self.bytes),
+                format_bytes(decoded)
+            ),
+            None => format_bytes(self.bytes),
+        }
+    }
+}
+
+/// Byte sizes of a document, with every list sorted from the largest object down.
+///
+/// Data shared by several objects is counted for each of them, so the entries of a list
+/// can add up to more than the total.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Sizes {
+    pub buffer_bytes: usize,
+    pub image_bytes: usize,
+    pub decoded_image_bytes: usize,
+    pub buffer_views: Vec<Entry>,
+    pub accessors: Vec<Entry>,
+    pub meshes: Vec<Entry>,
+    pub animations: Vec<Entry>,
+    pub images: Vec<Entry>,
+    pub textures: Vec<Entry>,
+    pub materials: Vec<Entry>,
+}
+
+impl Sizes {
+    pub fn from_document(document: &Document) -> Self {
+        let gltf = &document.document;
+
+        let accessor_bytes: Vec<usize> = gltf.accessors().map(|a| accessor_bytes(&a)).collect();
+        let accessors_total = |accessors: BTreeSet<usize>| -> usize {
+            accessors
+                .into_iter()
+                .map(|index| accessor_bytes[index])
+                .sum()
+        };
+
+        let image_bytes: Vec<(usize, usize)> = gltf
+            .images()
+            .map(|image| {
+                // Unreadable images count as empty; the size is read from the header
+                // rather than by decoding the whole image.
+                let encoded = document.encoded_image(image.index());
+                let encoded = encoded.as_deref().unwrap_or_default();
+                let decoded = image::io::Reader::new(Cursor::new(encoded))
+                    .with_guessed_format()
+                    .ok()
+                    .and_then(|reader| reader.into_dimensions().ok())
+                    .map_or(0, |(width, height)| width as usize * height as usize * 4);
+                (encoded.len(), decoded)
+            })
+            .collect();
+        let images_total = |images: BTreeSet<usize>| -> (usize, usize) {
+            images
+                .into_iter()
+                .fold((0, 0), |(encoded, decoded), index| {
+                    (
+                        encoded + image_bytes[index].0,
+                        decoded + image_bytes[index].1,
+                    )
+                })
+        };
+
+        let mut sizes = Self {
+            buffer_bytes: gltf.buffers().map(|buffer| buffer.length()).sum(),
+            image_bytes: image_bytes.iter().map(|&(encoded, _)| encoded).sum(),
+            decoded_image_bytes: image_bytes.iter().map(|&(_, decoded)| decoded).sum(),
+            buffer_views: gltf
+                .views()
+                .map(|view| entry(view.index(), view.name(), view.length(), None))
+                .collect(),
+            accessors: gltf
+                .accessors()
+                .map(|a| entry(a.index(), a.name(), accessor_bytes[a.index()], None))
+                .collect(),
+            meshes: gltf
+                .meshes()
+                .map(|mesh| {
+                    let bytes = accessors_total(mesh_accessors(&mesh));
+                    entry(mesh.index(), mesh.name(), bytes, None)
+                })
+                .collect(),
+            animations: gltf
+                .animations()
+                .map(|animation| {
+                    let accessors = animation
+                        .samplers()
+                        .flat_map(|sampler| vec![sampler.input().index(), sampler.output().index()])
+                        .collect();
+                    let bytes = accessors_total(accessors);
+                    entry(animation.index(), animation.name(), bytes, None)
+                })
+                .collect(),
+            images: gltf
+                .images()
+                .map(|image| {
+                    let (encoded, decoded) = image_bytes[image.index()];
+                    entry(image.index(), image.name(), encoded, Some(decoded))
+                })
+                .collect(),
+            textures: gltf
+                .textures()
+                .map(|texture| {
+                    let (encoded, decoded) = image_bytes[texture.source().index()];
+                    entry(texture.index(), texture.name(), encoded, Some(decoded))
+                })
+                .collect(),
+            materials: gltf
+                .materials()
+                .filter_map(|material| {
+                    let (encoded, decoded) = images_total(material_images(&material));
+                    Some(entry(
+                        material.index()?,
+                        material.name(),
+                        encoded,
+                        Some(decoded),
+                    ))
+                })
+                .collect(),
+        };
+
+        for entries in sizes.lists_mut() {
+            entries.sort_by(|a, b| {
+                (b.bytes, b.decoded_bytes, a.index).cmp(&(a.bytes, a.decoded_bytes, b.index))
+            });
+        }
+        sizes
+    }
+
+    /// Human-readable totals, in display order.
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("Buffers", format_bytes(self.buffer_bytes)),
+            ("Images", format_bytes(self.image_bytes)),
+            ("Decoded images", format_bytes(self.decoded_image_bytes)),
+        ]
+    }
+
+    /// The lists of objects with their headings, in display order.
+    pub fn lists(&self) -> Vec<(&'static str, &[Entry])> {
+        vec![
+            ("Meshes", &self.meshes),
+            ("Textures", &self.textures),
+            ("Materials", &self.materials),
+            ("Animations", &self.animations),
+            ("Images", &self.images),
+            ("Accessors", &self.accessors),
+            ("Buffer views", &self.buffer_views),
+        ]
+    }
+
+    pub fn lists_mut(&mut self) -> Vec<&mut Vec<Entry>> {
+        vec![
+            &mut self.buffer_views,
+            &mut self.accessors,
+            &mut self.meshes,
+            &mut self.animations,
+            &mut self.images,
+            &mut self.textures,
+            &mut self.materials,
+        ]
+    }
+}
+
+fn entry(index: usize, name: Option<&str>, bytes: usize, decoded_bytes: Option<usize>) -> Entry {
+    Entry {
+        index,
+        name: name.map(String::from),
+        bytes,
+        decoded_bytes,
+    }
+}
+
+/// The bytes an accessor reads, not counting the padding of interleaved buffer views.
+fn accessor_bytes(accessor: &gltf::Accessor<'_>) -> usize {
+    let dense = if accessor.view().is_some() {
+        accessor.count() * accessor.size()
+    } else {
+        0
+    };
+    let sparse = accessor.sparse().map_or(0, |sparse| {
+        sparse.count() as usize * (sparse.indices().index_type().size() + accessor.size())
+    });
+    dense + sparse
+}
+
+fn mesh_accessors(mesh: &gltf::Mesh<'_>) -> BTreeSet<usize> {
+    let mut accessors = BTreeSet::new();
+    for primitive in mesh.primitives() {
+        accessors.extend(primitive.attributes().map(|(_, accessor)| accessor.index()));
+        accessors.extend(primitive.indices().map(|accessor| accessor.index()));
+        for target in primitive.morph_targets() {
+            let target_accessors = vec![target.positions(), target.normals(), target.tangents()];
+            accessors.extend(target_accessors.into_iter().flatten().map(|a| a.index()));
+        }
+    }
+    accessors
+}
+
+fn material_images(material: &gltf::Material<'_>) -> BTreeSet<usize> {
+    let pbr = material.pbr_metallic_roughness();
+    vec![
+        pbr.base_color_texture().map(|info| info.texture()),
+        pbr.metallic_roughness_texture().map(|info| info.texture()),
+        material.normal_texture().map(|info| info.texture()),
+        material.occlusion_texture().map(|info| info.texture()),
+        material.emissive_texture().map(|info| info.texture()),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|texture| texture.source().index())
+    .collect()
+}
+
+/// Formats a byte count with a binary unit, like `1.5 MiB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
@@ -0,0 +1,61 @@
+use serde::Serialize;
+
+/// Resource counts for a glTF document, as shown in the "Document Statistics" panel.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Statistics {
+    pub accessors: usize,
+    pub animations: usize,
+    pub buffers: usize,
+    pub cameras: usize,
+    pub has_default_scene: bool,
+    pub extensions_used: usize,
+    pub extensions_required: usize,
+    pub images: usize,
+    pub materials: usize,
+    pub meshes: usize,
+    pub samplers: usize,
+    pub skins: usize,
+    pub textures: usize,
+    pub buffer_views: usize,
+}
+
+impl Statistics {
+    pub fn from_document(document: &gltf::Document) -> Self {
+        Self {
+            accessors: document.accessors().count(),
+            animations: document.animations().count(),
+            buffers: document.buffers().count(),
+            cameras: document.cameras().count(),
+            has_default_scene: document.default_scene().is_some(),
+            extensions_used: document.extensions_used().count(),
+            extensions_required: document.extensions_required().count(),
+            images: document.images().count(),
+            materials: document.materials().count(),
+            meshes: document.meshes().count(),
+            samplers: document.samplers().count(),
+            skins: document.skins().count(),
+            textures: document.textures().count(),
+            buffer_views: document.views().count(),
+        }
+    }
+
+    /// Human-readable labels and values, in display order.
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("Accessors", self.accessors.to_string()),
+            ("Animations", self.animations.to_string()),
+            ("Buffers", self.buffers.to_string()),
+            ("Cameras", self.cameras.to_string()),
+            ("Has default scene", self.has_default_scene.to_string()),
+            ("Extensions used", self.extensions_used.to_string()),
+            ("Extensions required", self.extensions_required.to_string()),
+            ("Images", self.images.to_string()),
+            ("Materials", self.materials.to_string()),
+            ("Meshes", self.meshes.to_string()),
+            ("Samplers", self.samplers.to_string()),
+            ("Skins", self.skins.to_string()),
+            ("Textures", self.textures.to_string()),
+            ("Buffer views", self.buffer_views.to_string()),
+        ]
+    }
+}
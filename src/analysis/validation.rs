@@ -0,0 +1,665 @@
+use std::fmt;
+
+use gltf::{
+    accessor::{DataType, Dimensions},
+    buffer::Target,
+};
+use serde::Serialize;
+
+use super::accessor;
+use crate::document::Document;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => f.write_str("error"),
+            Self::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    /// JSON pointer to the offending object, e.g. `/accessors/3`.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.pointer, self.message)
+    }
+}
+
+/// Checks the buffer and accessor data of an imported document against the glTF 2.0 rules
+/// that the `gltf` crate doesn't already enforce during import.
+pub fn validate(document: &Document) -> Vec<Issue> {
+    let mut validator = Validator {
+        document,
+        issues: Vec::new(),
+    };
+
+    validator.buffers();
+    validator.buffer_views();
+    validator.accessors();
+    validator.primitives();
+
+    validator.issues
+}
+
+struct Validator<'a> {
+    document: &'a Document,
+    issues: Vec<Issue>,
+}
+
+impl Validator<'_> {
+    fn error(&mut self, pointer: String, message: String) {
+        self.issues.push(Issue {
+            severity: Severity::Error,
+            pointer,
+            message,
+        });
+    }
+
+    fn warning(&mut self, pointer: String, message: String) {
+        self.issues.push(Issue {
+            severity: Severity::Warning,
+            pointer,
+            message,
+        });
+    }
+
+    fn buffers(&mut self) {
+        for buffer in self.document.document.buffers() {
+            let pointer = format!("/buffers/{}", buffer.index());
+            if let Err(error) = self.document.buffer(buffer.index()) {
+                self.error(pointer, error.to_string());
+            }
+        }
+    }
+
+    fn buffer_views(&mut self) {
+        for view in self.document.document.views() {
+            let pointer = format!("/bufferViews/{}", view.index());
+            let buffer_length = view.buffer().length();
+
+            if view.offset() + view.length() > buffer_length {
+                self.error(
+                    pointer.clone(),
+                    format!(
+                        "byte range {}..{} exceeds the length of buffer {} ({} bytes)",
+                        view.offset(),
+                        view.offset() + view.length(),
+                        view.buffer().index(),
+                        buffer_length
+                    ),
+                );
+            }
+
+            if let Some(stride) = view.stride() {
+                if !(4..=252).contains(&stride) || stride % 4 != 0 {
+                    self.error(
+                        format!("{}/byteStride", pointer),
+                        format!(
+                            "byteStride must be a multiple of 4 between 4 and 252, found {}",
+                            stride
+                        ),
+                    );
+                }
+                if view.target() == Some(Target::ElementArrayBuffer) {
+                    self.error(
+                        format!("{}/byteStride", pointer),
+                        String::from("index buffer views must not define byteStride"),
+                    );
+                }
+            }
+        }
+    }
+
+    fn accessors(&mut self) {
+        for accessor in self.document.document.accessors() {
+            let pointer = format!("/accessors/{}", accessor.index());
+            let element_size = accessor::element_size(accessor.dimensions(), accessor.data_type());
+            let mut in_bounds = true;
+
+            if let Some(view) = accessor.view() {
+                let stride = view.stride().unwrap_or(element_size);
+
+                if stride < element_size {
+                    self.error(
+                        pointer.clone(),
+                        format!(
+                            "element size of {} bytes exceeds the byteStride of buffer view {} ({} bytes)",
+                            element_size,
+                            view.index(),
+                            stride
+                        ),
+                    );
+                }
+
+                if (view.offset() + accessor.offset()) % accessor.data_type().size() != 0 {
+                    self.error(
+                        format!("{}/byteOffset", pointer),
+                        format!(
+                            "data is not aligned to its component size of {} bytes",
+                            accessor.data_type().size()
+                        ),
+                    );
+                }
+
+                if accessor.count() > 0 {
+                    let end = stride
+                        .checked_mul(accessor.count() - 1)
+                        .and_then(|end| end.checked_add(accessor.offset()))
+                        .and_then(|end| end.checked_add(element_size));
+                    match end {
+                        Some(end) if end <= view.length() => {}
+                        Some(end) => {
+                            in_bounds = false;
+                            self.error(
+                                pointer.clone(),
+                                format!(
+                                    "{} elements end at byte {}, past the end of buffer view {} ({} bytes)",
+                                    accessor.count(),
+                                    end,
+                                    view.index(),
+                                    view.length()
+                                ),
+                            );
+                        }
+                        None => {
+                            in_bounds = false;
+                            self.error(
+                                pointer.clone(),
+                                format!(
+                                    "{} elements with a stride of {} bytes are too large for any buffer view",
+                                    accessor.count(),
+                                    stride
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+
+            if let Some(sparse) = accessor.sparse() {
+                let indices = sparse.indices();
+                let index_size = match indices.index_type() {
+                    gltf::accessor::sparse::IndexType::U8 => 1,
+                    gltf::accessor::sparse::IndexType::U16 => 2,
+                    gltf::accessor::sparse::IndexType::U32 => 4,
+                };
+                let indices_end = (sparse.count() as usize)
+                    .checked_mul(index_size)
+                    .and_then(|end| end.checked_add(indices.offset() as usize));
+                if indices_end.map_or(true, |end| end > indices.view().length()) {
+                    in_bounds = false;
+                    self.error(
+                        format!("{}/sparse/indices", pointer),
+                        sparse_end_message("indices", indices_end, indices.view().index()),
+                    );
+                } else if let Some(sparse_indices) =
+                    accessor::sparse_indices(&sparse, self.document)
+                {
+                    let mut out_of_range = sparse_indices
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, &index)| index >= accessor.count());
+                    if let Some((position, &index)) = out_of_range.next() {
+                        in_bounds = false;
+                        self.error(
+                            format!("{}/sparse/indices", pointer),
+                            format!(
+                                "{} sparse indices of accessor {} are out of range for {} elements, the first being {} at position {}",
+                                1 + out_of_range.count(),
+                                accessor.index(),
+                                accessor.count(),
+                                index,
+                                position
+                            ),
+                        );
+                    }
+                }
+
+                let values = sparse.values();
+                let values_end = (sparse.count() as usize)
+                    .checked_mul(element_size)
+                    .and_then(|end| end.checked_add(values.offset() as usize));
+                if values_end.map_or(true, |end| end > values.view().length()) {
+                    in_bounds = false;
+                    self.error(
+                        format!("{}/sparse/values", pointer),
+                        sparse_end_message("values", values_end, values.view().index()),
+                    );
+                }
+            }
+
+            if in_bounds {
+                self.accessor_bounds(&accessor, &pointer);
+            }
+        }
+    }
+
+    fn accessor_bounds(&mut self, accessor: &gltf::Accessor<'_>, pointer: &str) {
+        let (declared_min, declared_max) = match (accessor.min(), accessor.max()) {
+            (Some(min), Some(max)) => (min, max),
+            (None, None) => return,
+            _ => {
+                self.error(
+                    pointer.to_owned(),
+                    String::from("min and max must be defined together"),
+                );
+                return;
+            }
+        };
+
+        let data = match accessor::read(accessor, self.document) {
+            Some(data) => data,
+            None => {
+                self.error(
+                    pointer.to_owned(),
+                    String::from("accessor data could not be read"),
+                );
+                return;
+            }
+        };
+        let (actual_min, actual_max) = match data.bounds() {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        for (name, declared, actual, is_min) in [
+            ("min", &declared_min, &actual_min, true),
+            ("max", &declared_max, &actual_max, false),
+        ] {
+            let pointer = format!("{}/{}", pointer, name);
+            let declared = match declared.as_array() {
+                Some(declared) if declared.len() == data.components => declared,
+                _ => {
+                    self.error(
+                        pointer,
+                        format!("must be an array of {} numbers", data.components),
+                    );
+                    continue;
+                }
+            };
+
+            for (component, (declared, &actual)) in declared.iter().zip(actual).enumerate() {
+                let declared = match declared.as_f64() {
+                    Some(declared) => declared,
+                    None => {
+                        self.error(
+                            format!("{}/{}", pointer, component),
+                            String::from("must be a number"),
+                        );
+                        continue;
+                    }
+                };
+
+                let tolerance = if accessor.data_type() == DataType::F32 {
+                    f64::from(f32::EPSILON) * actual.abs().max(1.0)
+                } else {
+                    0.0
+                };
+                let exceeded = if is_min {
+                    actual < declared - tolerance
+                } else {
+                    actual > declared + tolerance
+                };
+
+                if exceeded {
+                    self.error(
+                        format!("{}/{}", pointer, component),
+                        format!(
+                            "declared {} is {}, but the data contains {}",
+                            name, declared, actual
+                        ),
+                    );
+                } else if (declared - actual).abs() > tolerance {
+                    self.warning(
+                        format!("{}/{}", pointer, component),
+                        format!(
+                            "declared {} is {}, but the actual {} is {}",
+                            name, declared, name, actual
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    fn primitives(&mut self) {
+        for mesh in self.document.document.meshes() {
+            for primitive in mesh.primitives() {
+                let pointer = format!("/meshes/{}/primitives/{}", mesh.index(), primitive.index());
+
+                let mut vertex_count = None;
+                for (semantic, accessor) in primitive.attributes() {
+                    match vertex_count {
+                        None => vertex_count = Some(accessor.count()),
+                        Some(count) if count != accessor.count() => {
+                            // `Semantic` implements `ToString`, but not `Display`.
+                            let semantic = semantic.to_string();
+                            self.error(
+                                format!("{}/attributes/{}", pointer, semantic),
+                                format!(
+                                    "has {} elements, but other attributes have {}",
+                                    accessor.count(),
+                                    count
+                                ),
+                            )
+                        }
+                        Some(_) => {}
+                    }
+                }
+
+                if let Some(indices) = primitive.indices() {
+                    self.primitive_indices(&indices, vertex_count, &pointer);
+                }
+            }
+        }
+    }
+
+    fn primitive_indices(
+        &mut self,
+        indices: &gltf::Accessor<'_>,
+        vertex_count: Option<usize>,
+        pointer: &str,
+    ) {
+        let pointer = format!("{}/indices", pointer);
+
+        if indices.dimensions() != Dimensions::Scalar
+            || !matches!(
+                indices.data_type(),
+                DataType::U8 | DataType::U16 | DataType::U32
+            )
+        {
+            self.error(
+                pointer,
+                format!(
+                    "accessor {} must be an unsigned integer SCALAR accessor",
+                    indices.index()
+                ),
+            );
+            return;
+        }
+
+        let vertex_count = match vertex_count {
+            Some(vertex_count) => vertex_count,
+            None => return,
+        };
+
+        // Out of bounds reads have already been reported for the accessor itself.
+        if let Some(data) = accessor::read(indices, self.document) {
+            let mut out_of_range = data
+                .values
+                .iter()
+                .enumerate()
+                .filter(|(_, &index)| index as usize >= vertex_count);
+            if let Some((position, &index)) = out_of_range.next() {
+                self.error(
+                    pointer,
+                    format!(
+                        "{} indices of accessor {} are out of range for {} vertices, the first being {} at position {}",
+                        1 + out_of_range.count(),
+                        indices.index(),
+                        vertex_count,
+                        index,
+                        position
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Describes where sparse `part` ("indices" or "values") end, or that they cannot fit in
+/// a buffer view at all if the end overflows.
+fn sparse_end_message(part: &str, end: Option<usize>, view: usize) -> String {
+    match end {
+        Some(end) => format!(
+            "sparse {} end at byte {}, past the end of buffer view {}",
+            part, end, view
+        ),
+        None => format!("sparse {} are too large for any buffer view", part),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    /// Validates a document with one buffer holding `bytes` and the given buffer views,
+    /// accessors and meshes.
+    fn validate_json(bytes: &[u8], views: Value, accessors: Value, meshes: Value) -> Vec<Issue> {
+        let json = json!({
+            "asset": { "version": "2.0" },
+            "buffers": [{
+                "byteLength": bytes.len(),
+                "uri": format!("data:application/octet-stream;base64,{}", base64::encode(bytes)),
+            }],
+            "bufferViews": views,
+            "accessors": accessors,
+            "meshes": meshes,
+        });
+        validate(&Document::import_slice(json.to_string().as_bytes(), None).unwrap())
+    }
+
+    fn floats(values: &[f32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes().to_vec())
+            .collect()
+    }
+
+    fn pointers(issues: &[Issue]) -> Vec<(Severity, &str)> {
+        issues
+            .iter()
+            .map(|issue| (issue.severity, issue.pointer.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn valid_triangle() {
+        let mut bytes = floats(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        bytes.extend_from_slice(&[0, 1, 2, 0]);
+        let issues = validate_json(
+            &bytes,
+            json!([
+                { "buffer": 0, "byteLength": 36, "target": 34962 },
+                { "buffer": 0, "byteOffset": 36, "byteLength": 3, "target": 34963 },
+            ]),
+            json!([
+                {
+                    "bufferView": 0,
+                    "componentType": 5126,
+                    "count": 3,
+                    "type": "VEC3",
+                    "min": [0, 0, 0],
+                    "max": [1, 1, 0],
+                },
+                { "bufferView": 1, "componentType": 5121, "count": 3, "type": "SCALAR" },
+            ]),
+            json!([{ "primitives": [{ "attributes": { "POSITION": 0 }, "indices": 1 }] }]),
+        );
+        assert_eq!(issues, vec![]);
+    }
+
+    #[test]
+    fn elements_past_the_buffer_view() {
+        let issues = validate_json(
+            &floats(&[0.0, 1.0]),
+            json!([{ "buffer": 0, "byteLength": 8 }]),
+            json!([{
+                "bufferView": 0,
+                "componentType": 5126,
+                "count": 3,
+                "type": "SCALAR",
+                "min": [0],
+                "max": [1],
+            }]),
+            json!([]),
+        );
+        // The bounds are not checked against data that cannot be read.
+        assert_eq!(pointers(&issues), vec![(Severity::Error, "/accessors/0")]);
+        assert_eq!(
+            issues[0].message,
+            "3 elements end at byte 12, past the end of buffer view 0 (8 bytes)"
+        );
+    }
+
+    #[test]
+    fn huge_count_is_reported_without_reading() {
+        let issues = validate_json(
+            &floats(&[0.0]),
+            json!([{ "buffer": 0, "byteLength": 4, "byteStride": 252 }]),
+            json!([{
+                "bufferView": 0,
+                "componentType": 5126,
+                "count": u32::MAX,
+                "type": "SCALAR",
+                "min": [0],
+                "max": [0],
+            }]),
+            json!([]),
+        );
+        assert_eq!(pointers(&issues), vec![(Severity::Error, "/accessors/0")]);
+        assert!(issues[0].message.contains("past the end of buffer view 0"));
+    }
+
+    #[test]
+    fn sparse_indices_out_of_range() {
+        let mut bytes = vec![1, 7, 9, 0];
+        bytes.extend(floats(&[1.0, 2.0, 3.0]));
+        let issues = validate_json(
+            &bytes,
+            json!([
+                { "buffer": 0, "byteLength": 3 },
+                { "buffer": 0, "byteOffset": 4, "byteLength": 12 },
+            ]),
+            json!([{
+                "componentType": 5126,
+                "count": 4,
+                "type": "SCALAR",
+                "sparse": {
+                    "count": 3,
+                    "indices": { "bufferView": 0, "componentType": 5121 },
+                    "values": { "bufferView": 1 },
+                },
+            }]),
+            json!([]),
+        );
+        assert_eq!(
+            pointers(&issues),
+            vec![(Severity::Error, "/accessors/0/sparse/indices")]
+        );
+        assert_eq!(
+            issues[0].message,
+            "2 sparse indices of accessor 0 are out of range for 4 elements, the first being 7 at position 1"
+        );
+    }
+
+    #[test]
+    fn sparse_past_the_buffer_view() {
+        let issues = validate_json(
+            &[0, 1, 0, 0],
+            json!([
+                { "buffer": 0, "byteLength": 2 },
+                { "buffer": 0, "byteOffset": 2, "byteLength": 2 },
+            ]),
+            json!([{
+                "componentType": 5126,
+                "count": 4,
+                "type": "SCALAR",
+                "sparse": {
+                    "count": 2,
+                    "indices": { "bufferView": 0, "componentType": 5123 },
+                    "values": { "bufferView": 1 },
+                },
+            }]),
+            json!([]),
+        );
+        assert_eq!(
+            pointers(&issues),
+            vec![
+                (Severity::Error, "/accessors/0/sparse/indices"),
+                (Severity::Error, "/accessors/0/sparse/values"),
+            ]
+        );
+        assert_eq!(
+            issues[1].message,
+            "sparse values end at byte 8, past the end of buffer view 1"
+        );
+    }
+
+    #[test]
+    fn declared_bounds() {
+        let issues = validate_json(
+            &floats(&[-1.0, 2.0, 0.5, 3.0]),
+            json!([{ "buffer": 0, "byteLength": 16 }]),
+            json!([{
+                "bufferView": 0,
+                "componentType": 5126,
+                "count": 2,
+                "type": "VEC2",
+                "min": [-2, 2],
+                "max": [0.5, 2.5],
+            }]),
+            json!([]),
+        );
+        assert_eq!(
+            pointers(&issues),
+            vec![
+                (Severity::Warning, "/accessors/0/min/0"),
+                (Severity::Error, "/accessors/0/max/1"),
+            ]
+        );
+        assert_eq!(
+            issues[1].message,
+            "declared max is 2.5, but the data contains 3"
+        );
+    }
+
+    #[test]
+    fn primitive_indices_out_of_range() {
+        let mut bytes = floats(&[0.0; 6]);
+        bytes.extend_from_slice(&[0, 1, 4, 0]);
+        let issues = validate_json(
+            &bytes,
+            json!([
+                { "buffer": 0, "byteLength": 24 },
+                { "buffer": 0, "byteOffset": 24, "byteLength": 3 },
+            ]),
+            json!([
+                {
+                    "bufferView": 0,
+                    "componentType": 5126,
+                    "count": 2,
+                    "type": "VEC3",
+                    "min": [0, 0, 0],
+                    "max": [0, 0, 0],
+                },
+                { "bufferView": 1, "componentType": 5121, "count": 3, "type": "SCALAR" },
+            ]),
+            json!([{ "primitives": [{ "attributes": { "POSITION": 0 }, "indices": 1 }] }]),
+        );
+        assert_eq!(
+            pointers(&issues),
+            vec![(Severity::Error, "/meshes/0/primitives/0/indices")]
+        );
+        assert_eq!(
+            issues[0].message,
+            "1 indices of accessor 1 are out of range for 2 vertices, the first being 4 at position 2"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Reading glTF documents out of .zip archives, which assets are often delivered as.
+//!
+//! A file inside an archive is named by the path of the archive followed by the path of
+//! the entry, like `assets.zip/models/duck.gltf`. Relative URIs can then be resolved by
+//! joining paths as usual, and `read` finds the data whether or not it is in an archive.
+
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use zip::{result::ZipError, ZipArchive};
+
+use crate::document::has_gltf_extension;
+
+pub const ARCHIVE_FILE_EXTENSION: &str = "zip";
+
+/// Whether the file name ends in `.zip`, ignoring case.
+pub fn has_archive_extension(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        extension
+            .to_string_lossy()
+            .eq_ignore_ascii_case(ARCHIVE_FILE_EXTENSION)
+    })
+}
+
+/// Whether `path` is a .zip archive on disk, rather than a file inside one.
+pub fn is_archive(path: &Path) -> bool {
+    has_archive_extension(path) && path.is_file()
+}
+
+/// The paths of the .gltf and .glb files in an archive, sorted by name.
+pub fn gltf_entries(archive: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut zip = open(archive)?;
+    let mut entries = Vec::new();
+    for index in 0..zip.len() {
+        let file = zip.by_index(index).map_err(io_error)?;
+        // macOS adds resource forks under `__MACOSX/` that are named like the real files.
+        if file.is_dir() || file.name().starts_with("__MACOSX/") {
+            continue;
+        }
+
+        let path = file
+            .name()
+            .split('/')
+            .fold(archive.to_path_buf(), |path, part| path.join(part));
+        if has_gltf_extension(&path) && !has_archive_extension(&path) {
+            entries.push(path);
+        }
+    }
+
+    entries.sort();
+    Ok(entries)
+}
+
+/// Reads a whole file, which may be inside an archive.
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let (archive, entry) = match split(path) {
+        Some(split) => split,
+        None => return fs::read(path),
+    };
+
+    let mut zip = open(archive)?;
+    let mut file = zip.by_name(&entry).map_err(io_error)?;
+    // The size in the header is not trusted for allocating, since it can be anything.
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Whether a file, which may be inside an archive, exists.
+pub fn exists(path: &Path) -> bool {
+    match split(path) {
+        Some((archive, entry)) => open(archive).is_ok_and(|mut zip| zip.by_name(&entry).is_ok()),
+        None => path.exists(),
+    }
+}
+
+/// The size of a file, which may be inside an archive, or `None` if it doesn't exist.
+pub fn file_len(path: &Path) -> Option<u64> {
+    match split(path) {
+        Some((archive, entry)) => {
+            let mut zip = open(archive).ok()?;
+            let file = zip.by_name(&entry).ok()?;
+            Some(file.size())
+        }
+        None => fs::metadata(path)
+            .ok()
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| metadata.len()),
+    }
+}
+
+/// The file on disk that holds `path`: its archive, or `path` itself.
+pub fn containing_file(path: &Path) -> &Path {
+    split(path).map_or(path, |(archive, _)| archive)
+}
+
+/// Makes a path, which may be inside an archive, absolute and resolves symbolic links in
+/// the part on disk. The entry inside an archive is kept as it is, since it isn't a file
+/// that could be looked up.
+pub fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    let file = containing_file(path).canonicalize()?;
+    match split(path) {
+        Some((_, entry)) => Ok(entry.split('/').fold(file, |path, part| path.join(part))),
+        None => Ok(file),
+    }
+}
+
+/// Splits a path into the archive it leads into and the name of the entry, like
+/// `assets.zip/models/../duck.gltf` into `assets.zip` and `duck.gltf`.
+///
+/// Returns `None` for paths that don't lead into an archive, or out of it again.
+fn split(path: &Path) -> Option<(&Path, String)> {
+    let archive = path
+        .ancestors()
+        .skip(1)
+        .find(|ancestor| is_archive(ancestor))?;
+
+    let mut parts = Vec::new();
+    for component in path.strip_prefix(archive).ok()?.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str()?),
+            Component::ParentDir => {
+                parts.pop()?;
+            }
+            _ => {}
+        }
+    }
+
+    Some((archive, parts.join("/")))
+}
+
+fn open(archive: &Path) -> io::Result<ZipArchive<File>> {
+    ZipArchive::new(File::open(archive)?).map_err(io_error)
+}
+
+fn io_error(error: ZipError) -> io::Error {
+    match error {
+        ZipError::Io(error) => error,
+        ZipError::FileNotFound => io::Error::new(io::ErrorKind::NotFound, error),
+        error => io::Error::new(io::ErrorKind::InvalidData, error),
+    }
+}
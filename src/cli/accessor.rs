@@ -0,0 +1,53 @@
+use std::{
+    error::Error,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::Clap;
+
+use crate::{document::Document, export};
+
+/// Decode an accessor and print its elements as CSV
+///
+/// Normalized integer components are converted to floating point values unless `--raw`
+/// is given.
+#[derive(Clap)]
+pub struct Accessor {
+    file: PathBuf,
+    index: usize,
+    /// Write the CSV to this file instead of standard output
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+    /// Print normalized integer components as stored
+    #[clap(long)]
+    raw: bool,
+}
+
+impl Accessor {
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        let document = Document::import(&self.file)?;
+        document.check_extensions()?;
+        let accessor = document
+            .document
+            .accessors()
+            .nth(self.index)
+            .ok_or_else(|| format!("there is no accessor {}", self.index))?;
+        let data = export::read_accessor(&document, &accessor, !self.raw)?;
+
+        match &self.output {
+            Some(path) => {
+                let mut file = io::BufWriter::new(fs::File::create(path)?);
+                export::write_csv(&mut file, &accessor, &data)?;
+                file.flush()?;
+            }
+            None => {
+                let stdout = io::stdout();
+                export::write_csv(&mut stdout.lock(), &accessor, &data)?;
+            }
+        }
+
+        Ok(())
+    }
+}
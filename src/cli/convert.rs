@@ -0,0 +1,58 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use clap::Clap;
+
+use crate::{
+    document::Document,
+    transform::{
+        self,
+        convert::{self, Target},
+    },
+};
+
+/// Convert between .gltf and .glb
+///
+/// The output format is chosen by the extension of the output file. When writing .gltf,
+/// the buffer and images are written next to it unless `--embed` is given.
+#[derive(Clap)]
+pub struct Convert {
+    input: PathBuf,
+    output: PathBuf,
+    /// Embed the buffer and images of a .gltf output as data URIs
+    #[clap(long)]
+    embed: bool,
+}
+
+impl Convert {
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        let target = target(&self.output, self.embed)?;
+        let document = Document::import(&self.input)?;
+        let json = transform::read_json(&document.path)?;
+        convert::convert(
+            &document.buffers()?,
+            document.base_dir(),
+            json,
+            &self.output,
+            target,
+        )
+    }
+}
+
+/// The output format for `output`, chosen by its extension.
+pub fn target(output: &Path, embed: bool) -> Result<Target, Box<dyn Error>> {
+    let extension = output
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    let target = match extension.as_deref() {
+        Some("glb") => Target::Glb,
+        Some("gltf") => Target::Gltf { embed },
+        _ => return Err("the output file must end in .glb or .gltf".into()),
+    };
+    if embed && target == Target::Glb {
+        return Err("--embed only applies to .gltf output".into());
+    }
+    Ok(target)
+}
@@ -0,0 +1,47 @@
+use std::{
+    error::Error,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::Clap;
+
+use super::Format;
+use crate::{analysis::diff, document::Document};
+
+/// Compare the structure of two glTF documents
+///
+/// Exits with a non-zero status if the documents differ.
+#[derive(Clap)]
+pub struct Diff {
+    old: PathBuf,
+    new: PathBuf,
+    #[clap(long, arg_enum, default_value = "text")]
+    format: Format,
+}
+
+impl Diff {
+    /// Returns whether the documents are structurally identical.
+    pub fn run(&self) -> Result<bool, Box<dyn Error>> {
+        let old = Document::import(&self.old)?;
+        let new = Document::import(&self.new)?;
+        let changes = diff::diff(&old.document, &new.document);
+
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+
+        match self.format {
+            Format::Text => {
+                for change in &changes {
+                    writeln!(stdout, "{}", change)?;
+                }
+            }
+            Format::Json => {
+                serde_json::to_writer_pretty(&mut stdout, &changes)?;
+                writeln!(stdout)?;
+            }
+        }
+
+        Ok(changes.is_empty())
+    }
+}
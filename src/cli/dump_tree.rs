@@ -0,0 +1,341 @@
+use std::{
+    error::Error,
+    fmt::{self, Write as _},
+    io::{self, Write as _},
+    path::Path,
+};
+
+use clap::ArgEnum;
+use serde::Serialize;
+
+use super::Format;
+use crate::document::Document;
+
+const UNNAMED_NODE: &str = "<unnamed node>";
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Detail {
+    Index,
+    Mesh,
+    Camera,
+    Light,
+    Skin,
+    Transform,
+}
+
+pub fn dump_tree(
+    path: &Path,
+    base_dir: Option<&Path>,
+    format: Format,
+    details: &[Detail],
+    max_depth: Option<usize>,
+) -> Result<(), Box<dyn Error>> {
+    // The tree doesn't need buffer or image data, so files that are missing don't matter.
+    let document = Document::import_arg(path, base_dir)?;
+    if let Err(error) = document.check_extensions() {
+        eprintln!("Warning: {}", error);
+    }
+    for failure in document.failures() {
+        eprintln!("Warning: {}", failure);
+    }
+
+    let tree = SceneEntry::from_document(&document.document, details, max_depth);
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    match format {
+        Format::Text => write!(stdout, "{}", text_tree(&tree, details))?,
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut stdout, &tree)?;
+            writeln!(stdout)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn text_tree(scenes: &[SceneEntry<'_>], details: &[Detail]) -> String {
+    fn write_node(
+        out: &mut String,
+        node: &NodeEntry<'_>,
+        details: &[Detail],
+        prefix: &mut String,
+        last: bool,
+    ) {
+        let branch = if last { "└── " } else { "├── " };
+        write!(
+            out,
+            "{}{}{}",
+            prefix,
+            branch,
+            node.name.unwrap_or(UNNAMED_NODE)
+        )
+        .ok();
+        if details.contains(&Detail::Index) {
+            write!(out, " #{}", node.index).ok();
+        }
+        for annotation in node.annotations() {
+            write!(out, " [{}]", annotation).ok();
+        }
+        out.push('\n');
+
+        let prefix_len = prefix.len();
+        prefix.push_str(if last { "    " } else { "│   " });
+        let mut children = node.children.iter().peekable();
+        while let Some(child) = children.next() {
+            write_node(out, child, details, prefix, children.peek().is_none());
+        }
+        prefix.truncate(prefix_len);
+    }
+
+    let mut out = String::new();
+    let mut prefix = String::new();
+
+    for scene in scenes {
+        out.push_str(scene.name.unwrap_or("<unnamed scene>"));
+        if details.contains(&Detail::Index) {
+            write!(out, " #{}", scene.index).ok();
+        }
+        if scene.default {
+            out.push_str(" [default]");
+        }
+        out.push('\n');
+
+        let mut root_nodes = scene.children.iter().peekable();
+        while let Some(root_node) = root_nodes.next() {
+            write_node(
+                &mut out,
+                root_node,
+                details,
+                &mut prefix,
+                root_nodes.peek().is_none(),
+            );
+        }
+    }
+
+    out
+}
+
+#[derive(Serialize)]
+struct SceneEntry<'a> {
+    index: usize,
+    name: Option<&'a str>,
+    default: bool,
+    children: Vec<NodeEntry<'a>>,
+}
+
+impl<'a> SceneEntry<'a> {
+    fn from_document(
+        document: &'a gltf::Document,
+        details: &[Detail],
+        max_depth: Option<usize>,
+    ) -> Vec<Self> {
+        let default_scene = document.default_scene().map(|scene| scene.index());
+
+        document
+            .scenes()
+            .map(|scene| Self {
+                index: scene.index(),
+                name: scene.name(),
+                default: Some(scene.index()) == default_scene,
+                children: if max_depth.map_or(true, |max_depth| max_depth > 0) {
+                    scene
+                        .nodes()
+                        .map(|node| NodeEntry::from_node(node, details, 1, max_depth))
+                        .collect()
+                } else {
+                    Vec::new()
+                },
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
+struct NodeEntry<'a> {
+    index: usize,
+    name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mesh: Option<MeshEntry<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    camera: Option<ResourceEntry<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    light: Option<LightEntry<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skin: Option<ResourceEntry<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transform: Option<TransformEntry>,
+    children: Vec<NodeEntry<'a>>,
+}
+
+impl<'a> NodeEntry<'a> {
+    fn from_node(
+        node: gltf::Node<'a>,
+        details: &[Detail],
+        depth: usize,
+        max_depth: Option<usize>,
+    ) -> Self {
+        let children = if max_depth.map_or(true, |max_depth| depth < max_depth) {
+            node.children()
+                .map(|child| Self::from_node(child, details, depth + 1, max_depth))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            index: node.index(),
+            name: node.name(),
+            mesh: node
+                .mesh()
+                .filter(|_| details.contains(&Detail::Mesh))
+                .map(|mesh| MeshEntry {
+                    index: mesh.index(),
+                    name: mesh.name(),
+                    primitives: mesh.primitives().count(),
+                }),
+            camera: node
+                .camera()
+                .filter(|_| details.contains(&Detail::Camera))
+                .map(|camera| ResourceEntry {
+                    index: camera.index(),
+                    name: camera.name(),
+                }),
+            light: node
+                .light()
+                .filter(|_| details.contains(&Detail::Light))
+                .map(|light| LightEntry {
+                    index: light.index(),
+                    name: light.name(),
+                    kind: match light.kind() {
+                        gltf::khr_lights_punctual::Kind::Directional => "directional",
+                        gltf::khr_lights_punctual::Kind::Point => "point",
+                        gltf::khr_lights_punctual::Kind::Spot { .. } => "spot",
+                    },
+                }),
+            skin: node
+                .skin()
+                .filter(|_| details.contains(&Detail::Skin))
+                .map(|skin| ResourceEntry {
+                    index: skin.index(),
+                    name: skin.name(),
+                }),
+            transform: Some(node.transform())
+                .filter(|_| details.contains(&Detail::Transform))
+                .map(TransformEntry::from),
+            children,
+        }
+    }
+
+    fn annotations(&self) -> impl Iterator<Item = String> + '_ {
+        let mesh = self.mesh.as_ref().map(|mesh| {
+            let plural = if mesh.primitives == 1 { "" } else { "s" };
+            format!(
+                "mesh {}{}, {} primitive{}",
+                mesh.index,
+                QuotedName(mesh.name),
+                mesh.primitives,
+                plural
+            )
+        });
+        let camera = self
+            .camera
+            .as_ref()
+            .map(|camera| format!("camera {}{}", camera.index, QuotedName(camera.name)));
+        let light = self.light.as_ref().map(|light| {
+            format!(
+                "{} light {}{}",
+                light.kind,
+                light.index,
+                QuotedName(light.name)
+            )
+        });
+        let skin = self
+            .skin
+            .as_ref()
+            .map(|skin| format!("skin {}{}", skin.index, QuotedName(skin.name)));
+        let transform = self.transform.as_ref().map(ToString::to_string);
+
+        mesh.into_iter()
+            .chain(camera)
+            .chain(light)
+            .chain(skin)
+            .chain(transform)
+    }
+}
+
+#[derive(Serialize)]
+struct ResourceEntry<'a> {
+    index: usize,
+    name: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct MeshEntry<'a> {
+    index: usize,
+    name: Option<&'a str>,
+    primitives: usize,
+}
+
+#[derive(Serialize)]
+struct LightEntry<'a> {
+    index: usize,
+    name: Option<&'a str>,
+    kind: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum TransformEntry {
+    Matrix {
+        matrix: [[f32; 4]; 4],
+    },
+    Decomposed {
+        translation: [f32; 3],
+        rotation: [f32; 4],
+        scale: [f32; 3],
+    },
+}
+
+impl From<gltf::scene::Transform> for TransformEntry {
+    fn from(transform: gltf::scene::Transform) -> Self {
+        match transform {
+            gltf::scene::Transform::Matrix { matrix } => Self::Matrix { matrix },
+            gltf::scene::Transform::Decomposed {
+                translation,
+                rotation,
+                scale,
+            } => Self::Decomposed {
+                translation,
+                rotation,
+                scale,
+            },
+        }
+    }
+}
+
+impl fmt::Display for TransformEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Matrix { matrix } => write!(f, "matrix {:?}", matrix),
+            Self::Decomposed {
+                translation,
+                rotation,
+                scale,
+            } => write!(f, "T {:?} R {:?} S {:?}", translation, rotation, scale),
+        }
+    }
+}
+
+/// Formats an optional resource name as ` "name"`, or nothing if it is unnamed.
+struct QuotedName<'a>(Option<&'a str>);
+
+impl fmt::Display for QuotedName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(name) => write!(f, " {:?}", name),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::{
+    error::Error,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::Clap;
+
+use super::Format;
+use crate::{analysis::extensions, document, transform};
+
+/// List the extensions a glTF file uses, the objects using them, and whether the explorer
+/// supports them
+///
+/// Files that require an unsupported extension cannot be opened.
+#[derive(Clap)]
+pub struct Extensions {
+    file: PathBuf,
+    #[clap(long, arg_enum, default_value = "text")]
+    format: Format,
+}
+
+impl Extensions {
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        let file = document::resolve(&self.file)?;
+        let extensions = extensions::extensions(&transform::read_json(&file)?);
+
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+
+        match self.format {
+            Format::Text => {
+                if extensions.is_empty() {
+                    writeln!(stdout, "No extensions")?;
+                }
+                for extension in &extensions {
+                    writeln!(stdout, "{} ({})", extension.name, extension.status())?;
+                    for object in &extension.objects {
+                        let object = if object.is_empty() { "root" } else { object };
+                        writeln!(stdout, "  {}", object)?;
+                    }
+                }
+            }
+            Format::Json => {
+                serde_json::to_writer_pretty(&mut stdout, &extensions)?;
+                writeln!(stdout)?;
+            }
+        }
+
+        Ok(())
+    }
+}
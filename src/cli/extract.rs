@@ -0,0 +1,74 @@
+use std::{error::Error, fs, path::PathBuf};
+
+use clap::Clap;
+
+use crate::{document::Document, export};
+
+/// Write embedded images, buffers and buffer views to disk
+///
+/// Without any of `--images`, `--image`, `--buffers` or `--buffer-views`, everything is
+/// extracted.
+#[derive(Clap)]
+pub struct Extract {
+    file: PathBuf,
+    /// Directory to write the extracted files to
+    #[clap(short, long, default_value = ".")]
+    output_dir: PathBuf,
+    /// Extract all images as PNG files
+    #[clap(long)]
+    images: bool,
+    /// Extract the image with this index as a PNG file
+    #[clap(long)]
+    image: Vec<usize>,
+    /// Extract the raw bytes of every buffer
+    #[clap(long)]
+    buffers: bool,
+    /// Extract the raw bytes of every buffer view
+    #[clap(long)]
+    buffer_views: bool,
+}
+
+impl Extract {
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        let document = Document::import(&self.file)?;
+        fs::create_dir_all(&self.output_dir)?;
+
+        let everything =
+            !self.images && self.image.is_empty() && !self.buffers && !self.buffer_views;
+        let mut written = Vec::new();
+
+        if everything || self.images {
+            written.extend(export::write_images(
+                &document,
+                0..document.document.images().count(),
+                &self.output_dir,
+            )?);
+        } else if !self.image.is_empty() {
+            written.extend(export::write_images(
+                &document,
+                self.image.iter().copied(),
+                &self.output_dir,
+            )?);
+        }
+        if everything || self.buffers {
+            written.extend(export::write_buffers(
+                &document,
+                0..document.document.buffers().count(),
+                &self.output_dir,
+            )?);
+        }
+        if everything || self.buffer_views {
+            written.extend(export::write_buffer_views(
+                &document,
+                0..document.document.views().count(),
+                &self.output_dir,
+            )?);
+        }
+
+        for path in written {
+            println!("{}", path.display());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use std::{
+    error::Error,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::Clap;
+
+use super::stats::write_table;
+use crate::{
+    analysis::statistics::Statistics,
+    document::{self, Document, ImportError},
+    transform::{
+        self, convert,
+        merge::{self, Layout},
+    },
+};
+
+/// Combine several glTF files into one
+///
+/// By default every scene of every input is kept, and the default scene of the first input
+/// becomes the default scene. With `--root-nodes`, the output has a single scene with one
+/// node per input, named after its file, holding the default scene of that input. The
+/// output format is chosen by the extension of the output file.
+#[derive(Clap)]
+pub struct Merge {
+    #[clap(required = true, min_values = 2)]
+    inputs: Vec<PathBuf>,
+    #[clap(short, long)]
+    output: PathBuf,
+    /// Put each input under a new root node in a single scene
+    #[clap(long)]
+    root_nodes: bool,
+    /// Embed the buffer and images of a .gltf output as data URIs
+    #[clap(long)]
+    embed: bool,
+}
+
+impl Merge {
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        let target = super::convert::target(&self.output, self.embed)?;
+
+        let documents = self
+            .inputs
+            .iter()
+            .map(|path| {
+                let document = Document::import(path)?;
+                document.check_extensions()?;
+                Ok(document)
+            })
+            .collect::<Result<Vec<_>, ImportError>>()?;
+        let inputs = documents
+            .iter()
+            .map(|document| Ok((transform::read_json(&document.path)?, &**document)))
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+        let layout = if self.root_nodes {
+            Layout::RootNodes
+        } else {
+            Layout::Scenes
+        };
+        let (json, buffers) = merge::merge(inputs, layout)?;
+        // Every image URI is a data URI by now, so there is nothing to resolve.
+        convert::convert(&buffers, Path::new(""), json, &self.output, target)?;
+
+        let statistics = Statistics::from_document(&document::parse(&self.output)?.document);
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        writeln!(stdout, "{}", self.output.display())?;
+        write_table(&mut stdout, &statistics.rows())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use clap::ArgEnum;
+
+pub mod accessor;
+pub mod convert;
+pub mod diff;
+pub mod dump_tree;
+pub mod extensions;
+pub mod extract;
+pub mod merge;
+pub mod optimize;
+pub mod query;
+pub mod render;
+pub mod report;
+pub mod size;
+pub mod stats;
+pub mod validate;
+pub mod watch;
+
+#[derive(ArgEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
@@ -0,0 +1,76 @@
+use std::{
+    error::Error,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::Clap;
+
+use super::stats::write_table;
+use crate::{
+    analysis::statistics::Statistics,
+    document::{self, Document},
+    transform::{self, convert, prune},
+};
+
+/// Write a smaller copy of a glTF file
+///
+/// `--prune` removes nodes that are in no scene, and the meshes, skins, cameras,
+/// materials, textures, images, samplers, accessors and buffer views nothing in a scene
+/// uses. The output format is chosen by the extension of the output file.
+#[derive(Clap)]
+pub struct Optimize {
+    input: PathBuf,
+    #[clap(short, long)]
+    output: PathBuf,
+    /// Remove everything that no scene references
+    #[clap(long)]
+    prune: bool,
+    /// Embed the buffer and images of a .gltf output as data URIs
+    #[clap(long)]
+    embed: bool,
+}
+
+impl Optimize {
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        if !self.prune {
+            return Err("nothing to do; pass --prune".into());
+        }
+        let target = super::convert::target(&self.output, self.embed)?;
+
+        let document = Document::import(&self.input)?;
+        document.check_extensions()?;
+        let mut json = transform::read_json(&document.path)?;
+        prune::prune(&mut json);
+        convert::convert(
+            &document.buffers()?,
+            document.base_dir(),
+            json,
+            &self.output,
+            target,
+        )?;
+
+        let before = Statistics::from_document(&document.document);
+        let after = Statistics::from_document(&document::parse(&self.output)?.document);
+        let rows: Vec<(&str, String)> = before
+            .rows()
+            .into_iter()
+            .zip(after.rows())
+            .map(|((label, before), (_, after))| {
+                let value = if before == after {
+                    before
+                } else {
+                    format!("{} -> {}", before, after)
+                };
+                (label, value)
+            })
+            .collect();
+
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        writeln!(stdout, "{}", self.output.display())?;
+        write_table(&mut stdout, &rows)?;
+
+        Ok(())
+    }
+}
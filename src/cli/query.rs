@@ -0,0 +1,35 @@
+use std::{
+    error::Error,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::Clap;
+
+use crate::{analysis::query::Query as Expression, document};
+
+/// Evaluate a path expression such as `nodes[?mesh].name` and print the result as JSON
+///
+/// Steps are `.property`, `[index]`, `[*]` for every element, and filters like `[?mesh]`,
+/// `[?name == "Root"]`, `[?name != "Root"]` or `[?name ~ "Metal"]`, where `~` matches a
+/// substring or an array element.
+#[derive(Clap)]
+pub struct Query {
+    file: PathBuf,
+    expression: String,
+}
+
+impl Query {
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        let expression: Expression = self.expression.parse()?;
+        let gltf = document::parse(&self.file)?;
+        let result = expression.evaluate(&Expression::document_json(&gltf.document));
+
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        serde_json::to_writer_pretty(&mut stdout, &result)?;
+        writeln!(stdout)?;
+
+        Ok(())
+    }
+}
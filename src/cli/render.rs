@@ -0,0 +1,61 @@
+use std::{error::Error, fs, path::PathBuf};
+
+use clap::Clap;
+
+use crate::{
+    document::Document,
+    export,
+    render::{self, Options},
+    transform,
+};
+
+/// Render a PNG thumbnail of the default scene with a CPU rasterizer
+///
+/// Without `--camera`, the camera is placed to show every mesh in the scene. Without
+/// KHR_lights_punctual lights in the scene, a light behind the camera is used.
+#[derive(Clap)]
+pub struct Render {
+    file: PathBuf,
+    /// Where to write the PNG file [default: <file stem>_thumbnail.png next to the file]
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+    /// Render the scene with this index instead of the default scene
+    #[clap(long)]
+    scene: Option<usize>,
+    /// Look through the camera with this index, which must be used by a node in the scene
+    #[clap(long)]
+    camera: Option<usize>,
+    #[clap(long, default_value = "512")]
+    width: u32,
+    #[clap(long, default_value = "512")]
+    height: u32,
+}
+
+impl Render {
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        if self.width == 0 || self.height == 0 {
+            return Err("the image size must not be zero".into());
+        }
+
+        let document = Document::import(&self.file)?;
+        document.check_extensions()?;
+        let image = render::render(
+            &document,
+            &Options {
+                width: self.width,
+                height: self.height,
+                scene: self.scene,
+                camera: self.camera,
+            },
+        )?;
+
+        let output = self
+            .output
+            .clone()
+            .unwrap_or_else(|| transform::sibling_path(&self.file, "_thumbnail", "png"));
+        fs::write(&output, export::encode_png(&image)?)?;
+        println!("{}", output.display());
+
+        Ok(())
+    }
+}
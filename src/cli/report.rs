@@ -0,0 +1,219 @@
+use std::{
+    error::Error,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::{ArgEnum, Clap};
+use serde::Serialize;
+
+use crate::{
+    analysis::statistics::Statistics,
+    archive,
+    document::{self, Document},
+};
+
+/// Import every glTF file below a directory and write their statistics as one report
+///
+/// Files that cannot be read or imported are listed with their error instead of stopping
+/// the run.
+#[derive(Clap)]
+pub struct Report {
+    dir: PathBuf,
+    #[clap(long, arg_enum, default_value = "csv")]
+    format: ReportFormat,
+    /// Write the report to this file instead of standard output
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Serialize)]
+struct Summary {
+    imported: usize,
+    failures: usize,
+    entries: Vec<Entry>,
+}
+
+#[derive(Serialize)]
+struct Entry {
+    path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// The exit code the error would have for a single file, see `ImportError::exit_code`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i32>,
+    #[serde(flatten)]
+    statistics: Option<Statistics>,
+}
+
+impl Report {
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        let mut entries = Vec::new();
+        let mut files = Vec::new();
+        walk(&self.dir, &mut files, &mut entries);
+        files.sort();
+
+        for path in files {
+            let imported = Document::import(&path)
+                .and_then(|document| document.check_data().map(|()| document));
+            let entry = match imported {
+                Ok(document) => Entry {
+                    path,
+                    error: None,
+                    exit_code: None,
+                    statistics: Some(Statistics::from_document(&document.document)),
+                },
+                Err(error) => Entry {
+                    path,
+                    error: Some(error.to_string()),
+                    exit_code: Some(error.exit_code()),
+                    statistics: None,
+                },
+            };
+            entries.push(entry);
+        }
+
+        let summary = Summary {
+            imported: entries
+                .iter()
+                .filter(|entry| entry.statistics.is_some())
+                .count(),
+            failures: entries.iter().filter(|entry| entry.error.is_some()).count(),
+            entries,
+        };
+
+        match &self.output {
+            Some(path) => {
+                let mut file = io::BufWriter::new(fs::File::create(path)?);
+                self.write(&mut file, &summary)?;
+                file.flush()?;
+            }
+            None => {
+                let stdout = io::stdout();
+                self.write(&mut stdout.lock(), &summary)?;
+            }
+        }
+
+        eprintln!(
+            "{}: {} file(s) imported, {} failure(s)",
+            self.dir.display(),
+            summary.imported,
+            summary.failures
+        );
+
+        Ok(())
+    }
+
+    fn write<W: Write>(&self, out: &mut W, summary: &Summary) -> Result<(), Box<dyn Error>> {
+        match self.format {
+            ReportFormat::Csv => write_csv(out, &summary.entries)?,
+            ReportFormat::Json => {
+                serde_json::to_writer_pretty(&mut *out, summary)?;
+                writeln!(out)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Collects the glTF files below `dir`, including those in .zip archives, recording
+/// directories and archives that cannot be read as failed entries.
+fn walk(dir: &Path, files: &mut Vec<PathBuf>, entries: &mut Vec<Entry>) {
+    let read_dir = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(error) => {
+            entries.push(Entry {
+                path: dir.to_path_buf(),
+                error: Some(error.to_string()),
+                exit_code: None,
+                statistics: None,
+            });
+            return;
+        }
+    };
+
+    for dir_entry in read_dir {
+        let dir_entry = match dir_entry {
+            Ok(dir_entry) => dir_entry,
+            Err(error) => {
+                entries.push(Entry {
+                    path: dir.to_path_buf(),
+                    error: Some(error.to_string()),
+                    exit_code: None,
+                    statistics: None,
+                });
+                continue;
+            }
+        };
+
+        let path = dir_entry.path();
+        // Symbolic links to directories are not followed, so cycles cannot occur.
+        if dir_entry
+            .file_type()
+            .is_ok_and(|file_type| file_type.is_dir())
+        {
+            walk(&path, files, entries);
+        } else if archive::has_archive_extension(&path) {
+            match archive::gltf_entries(&path) {
+                Ok(archive_entries) => files.extend(archive_entries),
+                Err(error) => entries.push(Entry {
+                    path,
+                    error: Some(error.to_string()),
+                    exit_code: None,
+                    statistics: None,
+                }),
+            }
+        } else if document::has_gltf_extension(&path) {
+            files.push(path);
+        }
+    }
+}
+
+fn write_csv<W: Write>(out: &mut W, entries: &[Entry]) -> io::Result<()> {
+    let labels = Statistics::default().rows();
+    write!(out, "path,error,exit_code")?;
+    for (label, _) in &labels {
+        write!(out, ",{}", csv_field(label))?;
+    }
+    writeln!(out)?;
+
+    for entry in entries {
+        write!(
+            out,
+            "{},{},{}",
+            csv_field(&entry.path.to_string_lossy()),
+            csv_field(entry.error.as_deref().unwrap_or("")),
+            entry
+                .exit_code
+                .map(|code| code.to_string())
+                .unwrap_or_default()
+        )?;
+        match &entry.statistics {
+            Some(statistics) => {
+                for (_, value) in statistics.rows() {
+                    write!(out, ",{}", value)?;
+                }
+            }
+            None => write!(out, "{}", ",".repeat(labels.len()))?,
+        }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
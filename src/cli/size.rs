@@ -0,0 +1,63 @@
+use std::{
+    error::Error,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::Clap;
+
+use super::{stats::write_table, Format};
+use crate::{analysis::size::Sizes, document::Document};
+
+/// Print where the bytes of a glTF file go, largest first
+///
+/// Buffer bytes are attributed to buffer views, accessors, meshes and animations, and
+/// image bytes, both encoded and decoded to RGBA, to images, textures and materials. Data
+/// shared by several objects is counted for each of them.
+#[derive(Clap)]
+pub struct Size {
+    file: PathBuf,
+    #[clap(long, arg_enum, default_value = "text")]
+    format: Format,
+    /// Only print this many of the largest objects of each kind
+    #[clap(long)]
+    limit: Option<usize>,
+}
+
+impl Size {
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        let document = Document::import(&self.file)?;
+        let mut sizes = Sizes::from_document(&document);
+        if let Some(limit) = self.limit {
+            for entries in sizes.lists_mut() {
+                entries.truncate(limit);
+            }
+        }
+
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+
+        match self.format {
+            Format::Text => {
+                write_table(&mut stdout, &sizes.rows())?;
+                for (heading, entries) in sizes.lists() {
+                    if entries.is_empty() {
+                        continue;
+                    }
+                    writeln!(stdout, "\n{}", heading)?;
+                    let rows: Vec<_> = entries
+                        .iter()
+                        .map(|entry| (entry.label(), entry.size()))
+                        .collect();
+                    write_table(&mut stdout, &rows)?;
+                }
+            }
+            Format::Json => {
+                serde_json::to_writer_pretty(&mut stdout, &sizes)?;
+                writeln!(stdout)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use std::{
+    error::Error,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::Clap;
+
+use super::{watch::watch, Format};
+use crate::{analysis::statistics::Statistics, document};
+
+/// Print the same resource counts as the "Document Statistics" panel
+#[derive(Clap)]
+pub struct Stats {
+    file: PathBuf,
+    #[clap(long, arg_enum, default_value = "text")]
+    format: Format,
+    /// Print the statistics again whenever the file changes on disk
+    #[clap(long)]
+    watch: bool,
+}
+
+impl Stats {
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        if self.watch {
+            watch(&self.file, None, || self.print())
+        } else {
+            self.print()
+        }
+    }
+
+    fn print(&self) -> Result<(), Box<dyn Error>> {
+        let gltf = document::parse(&self.file)?;
+        let statistics = Statistics::from_document(&gltf.document);
+
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+
+        match self.format {
+            Format::Text => write_table(&mut stdout, &statistics.rows())?,
+            Format::Json => {
+                serde_json::to_writer_pretty(&mut stdout, &statistics)?;
+                writeln!(stdout)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes `label: value` rows with the values aligned in a single column.
+pub fn write_table<W: Write, L: AsRef<str>>(out: &mut W, rows: &[(L, String)]) -> io::Result<()> {
+    let width = rows
+        .iter()
+        .map(|(label, _)| label.as_ref().len())
+        .max()
+        .unwrap_or(0);
+    for (label, value) in rows {
+        writeln!(
+            out,
+            "{:<width$}  {}",
+            format!("{}:", label.as_ref()),
+            value,
+            width = width + 1
+        )?;
+    }
+    Ok(())
+}
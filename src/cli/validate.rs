@@ -0,0 +1,78 @@
+use std::{
+    error::Error,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::Clap;
+use serde::Serialize;
+
+use super::Format;
+use crate::{
+    analysis::validation::{self, Issue, Severity},
+    document::Document,
+};
+
+/// Check buffer and accessor data against the glTF 2.0 rules
+///
+/// Exits with a non-zero status if any errors are found.
+#[derive(Clap)]
+pub struct Validate {
+    file: PathBuf,
+    #[clap(long, arg_enum, default_value = "text")]
+    format: Format,
+}
+
+impl Validate {
+    /// Returns whether the document passed validation without errors.
+    pub fn run(&self) -> Result<bool, Box<dyn Error>> {
+        let document = Document::import(&self.file)?;
+        document.check_extensions()?;
+        document.check_data()?;
+        let issues = validation::validate(&document);
+
+        let report = Report {
+            errors: count(&issues, Severity::Error),
+            warnings: count(&issues, Severity::Warning),
+            issues,
+        };
+
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+
+        match self.format {
+            Format::Text => {
+                for issue in &report.issues {
+                    writeln!(stdout, "{}", issue)?;
+                }
+                writeln!(
+                    stdout,
+                    "{}: {} error(s), {} warning(s)",
+                    self.file.display(),
+                    report.errors,
+                    report.warnings
+                )?;
+            }
+            Format::Json => {
+                serde_json::to_writer_pretty(&mut stdout, &report)?;
+                writeln!(stdout)?;
+            }
+        }
+
+        Ok(report.errors == 0)
+    }
+}
+
+#[derive(Serialize)]
+struct Report {
+    errors: usize,
+    warnings: usize,
+    issues: Vec<Issue>,
+}
+
+fn count(issues: &[Issue], severity: Severity) -> usize {
+    issues
+        .iter()
+        .filter(|issue| issue.severity == severity)
+        .count()
+}
@@ -0,0 +1,48 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    thread,
+};
+
+use crate::{
+    document::{Document, STDIN},
+    watch::{Watcher, POLL_INTERVAL},
+};
+
+/// Runs `f`, then again every time `file` or a file its relative URIs name changes on
+/// disk, until the process is interrupted. The URIs are resolved against `base` if
+/// given.
+///
+/// Errors are reported without stopping, since a file that is being saved may briefly be
+/// incomplete.
+pub fn watch(
+    file: &Path,
+    base: Option<&Path>,
+    mut f: impl FnMut() -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    if file == Path::new(STDIN) {
+        return Err("standard input cannot be watched".into());
+    }
+
+    loop {
+        // Set up before `f` reads the files, so that changes made meanwhile are noticed.
+        let mut watcher = Watcher::new(watched_files(file, base));
+        if let Err(error) = f() {
+            eprintln!("Error: {}", error);
+        }
+        while !watcher.changed() {
+            thread::sleep(POLL_INTERVAL);
+        }
+        eprintln!("{} changed, reloading", file.display());
+    }
+}
+
+/// `file` and the files its buffers and images are read from, which may be different
+/// after every change. Only `file` is watched while it cannot be imported.
+fn watched_files(file: &Path, base: Option<&Path>) -> Vec<PathBuf> {
+    let mut files = vec![file.to_path_buf()];
+    if let Ok(document) = Document::import_arg(file, base) {
+        files.extend(document.dependencies());
+    }
+    files
+}
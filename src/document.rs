@@ -0,0 +1,661 @@
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+};
+
+use image::{DynamicImage, GenericImageView, ImageFormat};
+
+use crate::{archive, transform};
+
+/// The files the explorer opens: glTF files, and .zip archives holding them.
+pub const GLTF_FILE_EXTENSIONS: &[&str] = &["glb", "gltf", archive::ARCHIVE_FILE_EXTENSION];
+
+/// What the `file` argument is set to for reading a document from standard input.
+pub const STDIN: &str = "-";
+
+/// A glTF document whose buffers and images are loaded when first used, and then kept.
+#[derive(Debug, Clone)]
+pub struct Document {
+    /// The file the document was read from, or `-` if it was read from memory. Files in
+    /// an archive are named like `assets.zip/scene.gltf`.
+    pub path: PathBuf,
+    pub document: gltf::Document,
+    base: PathBuf,
+    buffers: Vec<OnceLock<Result<Vec<u8>, ImportError>>>,
+    images: Vec<OnceLock<Result<gltf::image::Data, ImportError>>>,
+}
+
+impl Document {
+    /// Reads and validates the JSON of a .gltf or .glb file, leaving the buffers and
+    /// images for later. A .zip archive is opened if it holds exactly one glTF file.
+    ///
+    /// Fails if a buffer or image refers to a file that does not exist.
+    pub fn import<P: AsRef<Path>>(file: P) -> Result<Arc<Self>, ImportError> {
+        let file = resolve(file.as_ref())?;
+        Self::from_gltf(parse_file(&file)?, &file, base_of(&file), false)
+    }
+
+    /// Like `import`, but records buffers and images that refer to missing files instead
+    /// of failing, so that the rest of a broken document can still be inspected.
+    ///
+    /// See `failures` for what could not be loaded.
+    pub fn import_tolerant<P: AsRef<Path>>(file: P) -> Result<Arc<Self>, ImportError> {
+        let file = resolve(file.as_ref())?;
+        Self::from_gltf(parse_file(&file)?, &file, base_of(&file), true)
+    }
+
+    /// Like `import`, but for the contents of a .gltf or .glb file that is already in
+    /// memory. Relative URIs are resolved against `base`, or the current directory.
+    pub fn import_slice(data: &[u8], base: Option<&Path>) -> Result<Arc<Self>, ImportError> {
+        let gltf = parse_slice(data, Path::new(STDIN))?;
+        Self::from_gltf(
+            gltf,
+            Path::new(STDIN),
+            base.unwrap_or_else(|| Path::new("")),
+            false,
+        )
+    }
+
+    /// Like `import_slice`, but tolerates missing files like `import_tolerant`.
+    pub fn import_slice_tolerant(
+        data: &[u8],
+        base: Option<&Path>,
+    ) -> Result<Arc<Self>, ImportError> {
+        let gltf = parse_slice(data, Path::new(STDIN))?;
+        Self::from_gltf(
+            gltf,
+            Path::new(STDIN),
+            base.unwrap_or_else(|| Path::new("")),
+            true,
+        )
+    }
+
+    /// Imports the `file` given on the command line like `import_tolerant`, reading
+    /// standard input if it is `-`. Relative URIs are resolved against `base` if given.
+    pub fn import_arg(file: &Path, base: Option<&Path>) -> Result<Arc<Self>, ImportError> {
+        if file != Path::new(STDIN) {
+            let file = resolve(file)?;
+            let base = base.unwrap_or_else(|| base_of(&file));
+            return Self::from_gltf(parse_file(&file)?, &file, base, true);
+        }
+
+        let mut data = Vec::new();
+        io::stdin()
+            .read_to_end(&mut data)
+            .map_err(|error| ImportError::Io {
+                path: file.to_path_buf(),
+                source: Arc::new(error),
+            })?;
+        Self::import_slice_tolerant(&data, base)
+    }
+
+    fn from_gltf(
+        gltf: gltf::Gltf,
+        path: &Path,
+        base: &Path,
+        tolerant: bool,
+    ) -> Result<Arc<Self>, ImportError> {
+        let gltf::Gltf { document, mut blob } = gltf;
+        let mut missing = missing_uris(&document, base);
+        if !tolerant && !missing.is_empty() {
+            return Err(missing.swap_remove(0));
+        }
+
+        let buffers: Vec<_> = document
+            .buffers()
+            .map(|buffer| {
+                let data = OnceLock::new();
+                // The binary chunk has been read already.
+                if let gltf::buffer::Source::Bin = buffer.source() {
+                    let _ = data.set(
+                        blob.take()
+                            .ok_or_else(|| {
+                                ImportError::Buffer(String::from(
+                                    "the .glb file has no binary chunk",
+                                ))
+                            })
+                            .and_then(|blob| check_length(&buffer, blob)),
+                    );
+                }
+                data
+            })
+            .collect();
+        let images: Vec<_> = document.images().map(|_| OnceLock::new()).collect();
+        for error in missing {
+            if let ImportError::MissingUri { kind, index, .. } = &error {
+                let (kind, index) = (*kind, *index);
+                if kind == "buffer" {
+                    let _ = buffers[index].set(Err(error));
+                } else {
+                    let _ = images[index].set(Err(error));
+                }
+            }
+        }
+
+        Ok(Arc::new(Document {
+            path: path.to_path_buf(),
+            document,
+            base: base.to_path_buf(),
+            buffers,
+            images,
+        }))
+    }
+
+    /// The extensions in `extensionsRequired` that the explorer cannot interpret. The
+    /// document can still be explored, but data that depends on them is misread.
+    pub fn unsupported_extensions(&self) -> Vec<&str> {
+        self.document
+            .extensions_required()
+            .filter(|extension| !is_supported_extension(extension))
+            .collect()
+    }
+
+    /// Fails if there are `unsupported_extensions`, for commands whose results would be
+    /// wrong without them.
+    pub fn check_extensions(&self) -> Result<(), ImportError> {
+        let unsupported = self.unsupported_extensions();
+        if unsupported.is_empty() {
+            Ok(())
+        } else {
+            Err(ImportError::UnsupportedExtensions(
+                unsupported.into_iter().map(String::from).collect(),
+            ))
+        }
+    }
+
+    /// The directory relative URIs in the document are resolved against, which is inside
+    /// the archive for documents read from one.
+    pub fn base_dir(&self) -> &Path {
+        &self.base
+    }
+
+    /// The files that buffers and images are read from through relative URIs, which
+    /// change the document as much as the file itself does.
+    pub fn dependencies(&self) -> Vec<PathBuf> {
+        uri_files(&self.document, &self.base)
+            .into_iter()
+            .map(|(_, _, _, path)| path)
+            .collect()
+    }
+
+    /// The data of a buffer, read on first use.
+    pub fn buffer(&self, index: usize) -> Result<&[u8], ImportError> {
+        let (buffer, data) = self
+            .document
+            .buffers()
+            .nth(index)
+            .zip(self.buffers.get(index))
+            .ok_or_else(|| ImportError::Buffer(format!("there is no buffer {}", index)))?;
+        match data.get_or_init(|| self.load_buffer(&buffer)) {
+            Ok(data) => Ok(data),
+            Err(error) => Err(error.clone()),
+        }
+    }
+
+    /// The data of every buffer, reading those not used before.
+    pub fn buffers(&self) -> Result<Vec<&[u8]>, ImportError> {
+        (0..self.buffers.len())
+            .map(|index| self.buffer(index))
+            .collect()
+    }
+
+    /// The pixels of an image, decoded on first use.
+    pub fn image(&self, index: usize) -> Result<&gltf::image::Data, ImportError> {
+        let (image, data) = self
+            .document
+            .images()
+            .nth(index)
+            .zip(self.images.get(index))
+            .ok_or_else(|| ImportError::ImageDecode(format!("there is no image {}", index)))?;
+        match data.get_or_init(|| self.decode_image(&image)) {
+            Ok(data) => Ok(data),
+            Err(error) => Err(error.clone()),
+        }
+    }
+
+    /// The PNG or JPEG data of an image, without decoding it.
+    pub fn encoded_image(&self, index: usize) -> Result<Cow<'_, [u8]>, ImportError> {
+        if let Some(Err(error)) = self.images.get(index).and_then(OnceLock::get) {
+            return Err(error.clone());
+        }
+        let image = self
+            .document
+            .images()
+            .nth(index)
+            .ok_or_else(|| ImportError::ImageDecode(format!("there is no image {}", index)))?;
+        match image.source() {
+            gltf::image::Source::View { view, .. } => {
+                let buffer = self.buffer(view.buffer().index())?;
+                let bytes = buffer
+                    .get(view.offset()..view.offset() + view.length())
+                    .ok_or_else(|| {
+                        ImportError::ImageDecode(format!(
+                            "image {}: buffer view {} is out of bounds",
+                            index,
+                            view.index()
+                        ))
+                    })?;
+                Ok(Cow::Borrowed(bytes))
+            }
+            gltf::image::Source::Uri { uri, .. } => transform::read_uri(self.base_dir(), uri)
+                .map(Cow::Owned)
+                .map_err(|error| ImportError::Image(format!("image {}: {}", index, error))),
+        }
+    }
+
+    /// Loads every buffer and decodes every image ahead of use, calling `progress` after
+    /// each. Stops early and returns `false` as soon as `progress` returns `false`.
+    ///
+    /// Data that cannot be loaded is recorded like on first use, see `failures`.
+    pub fn load_all(&self, mut progress: impl FnMut(Progress) -> bool) -> bool {
+        let total = self.buffers.len();
+        for index in 0..total {
+            let _ = self.buffer(index);
+            let loaded = index + 1;
+            if !progress(Progress::Buffer { loaded, total }) {
+                return false;
+            }
+        }
+
+        let total = self.images.len();
+        for index in 0..total {
+            let _ = self.image(index);
+            let decoded = index + 1;
+            if !progress(Progress::Image { decoded, total }) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Loads every buffer and image, failing with the first that cannot be loaded, for
+    /// commands that report on all of the data.
+    pub fn check_data(&self) -> Result<(), ImportError> {
+        self.load_all(|_| true);
+        match self.failures().into_iter().next() {
+            Some(failure) => Err(failure.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// The errors of the buffers and images that could not be loaded so far. Missing files
+    /// are known from the start, other problems once the data is first used.
+    pub fn failures(&self) -> Vec<&ImportError> {
+        let buffers = self
+            .buffers
+            .iter()
+            .filter_map(|data| data.get()?.as_ref().err());
+        let images = self
+            .images
+            .iter()
+            .filter_map(|data| data.get()?.as_ref().err());
+        buffers.chain(images).collect()
+    }
+
+    fn load_buffer(&self, buffer: &gltf::Buffer<'_>) -> Result<Vec<u8>, ImportError> {
+        match buffer.source() {
+            gltf::buffer::Source::Bin => Err(ImportError::Buffer(String::from(
+                "the .glb file has no binary chunk",
+            ))),
+            gltf::buffer::Source::Uri(uri) => {
+                let data = transform::read_uri(self.base_dir(), uri).map_err(|error| {
+                    ImportError::Buffer(format!("buffer {}: {}", buffer.index(), error))
+                })?;
+                check_length(buffer, data)
+            }
+        }
+    }
+
+    /// Decodes a PNG or JPEG image into the pixel layout `gltf::import` would use.
+    fn decode_image(&self, image: &gltf::Image<'_>) -> Result<gltf::image::Data, ImportError> {
+        let decode_error = |message: String| {
+            ImportError::ImageDecode(format!("image {}: {}", image.index(), message))
+        };
+
+        let encoded = self.encoded_image(image.index())?;
+        let mime_type = match image.source() {
+            gltf::image::Source::View { mime_type, .. } => Some(mime_type),
+            gltf::image::Source::Uri { uri, mime_type } => {
+                mime_type.or_else(|| transform::data_uri_mime_type(uri))
+            }
+        };
+        let format = match mime_type {
+            Some("image/png") => ImageFormat::Png,
+            Some("image/jpeg") => ImageFormat::Jpeg,
+            _ => match image::guess_format(&encoded) {
+                Ok(format @ ImageFormat::Png) | Ok(format @ ImageFormat::Jpeg) => format,
+                _ => return Err(decode_error(String::from("not a PNG or JPEG image"))),
+            },
+        };
+        let decoded = image::load_from_memory_with_format(&encoded, format)
+            .map_err(|error| decode_error(error.to_string()))?;
+
+        let format = match decoded {
+            DynamicImage::ImageLuma8(_) => gltf::image::Format::R8,
+            DynamicImage::ImageLumaA8(_) => gltf::image::Format::R8G8,
+            DynamicImage::ImageRgb8(_) => gltf::image::Format::R8G8B8,
+            DynamicImage::ImageRgba8(_) => gltf::image::Format::R8G8B8A8,
+            DynamicImage::ImageBgr8(_) => gltf::image::Format::B8G8R8,
+            DynamicImage::ImageBgra8(_) => gltf::image::Format::B8G8R8A8,
+            DynamicImage::ImageLuma16(_) => gltf::image::Format::R16,
+            DynamicImage::ImageLumaA16(_) => gltf::image::Format::R16G16,
+            DynamicImage::ImageRgb16(_) => gltf::image::Format::R16G16B16,
+            DynamicImage::ImageRgba16(_) => gltf::image::Format::R16G16B16A16,
+        };
+        let (width, height) = decoded.dimensions();
+        Ok(gltf::image::Data {
+            pixels: decoded.to_bytes(),
+            format,
+            width,
+            height,
+        })
+    }
+}
+
+/// A step of an import that has been completed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Progress {
+    Parsed,
+    Buffer { loaded: usize, total: usize },
+    Image { decoded: usize, total: usize },
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parsed => f.write_str("JSON parsed"),
+            Self::Buffer { loaded, total } => write!(f, "buffer {}/{} loaded", loaded, total),
+            Self::Image { decoded, total } => write!(f, "image {}/{} decoded", decoded, total),
+        }
+    }
+}
+
+/// Reads and validates the JSON of a .gltf or .glb file, without loading buffers or images.
+pub fn parse(file: &Path) -> Result<gltf::Gltf, ImportError> {
+    parse_file(&resolve(file)?)
+}
+
+/// The glTF file to open for `file`: the only glTF file in a .zip archive, or `file`
+/// itself if it is not an archive.
+///
+/// Fails if the archive holds no glTF file or several, so that one can be picked.
+pub fn resolve(file: &Path) -> Result<Cow<'_, Path>, ImportError> {
+    if !archive::is_archive(file) {
+        return Ok(Cow::Borrowed(file));
+    }
+
+    let mut entries = archive::gltf_entries(file).map_err(|error| ImportError::Io {
+        path: file.to_path_buf(),
+        source: Arc::new(error),
+    })?;
+    if entries.len() == 1 {
+        Ok(Cow::Owned(entries.remove(0)))
+    } else {
+        Err(ImportError::Archive {
+            path: file.to_path_buf(),
+            entries,
+        })
+    }
+}
+
+/// Like `parse`, for a file that is not an archive.
+fn parse_file(file: &Path) -> Result<gltf::Gltf, ImportError> {
+    let bytes = archive::read(file).map_err(|error| ImportError::Io {
+        path: file.to_path_buf(),
+        source: Arc::new(error),
+    })?;
+    parse_slice(&bytes, file)
+}
+
+/// Like `parse`, for the contents of a file. Binary glTF is told from JSON by its magic
+/// bytes, whatever the file is named.
+fn parse_slice(data: &[u8], file: &Path) -> Result<gltf::Gltf, ImportError> {
+    gltf::Gltf::from_slice(data).map_err(|error| ImportError::from_gltf(error, file))
+}
+
+/// The directory relative URIs in `file` are resolved against.
+fn base_of(file: &Path) -> &Path {
+    file.parent().unwrap_or_else(|| Path::new(""))
+}
+
+/// Whether the explorer understands the glTF extension, rather than ignoring its data.
+pub fn is_supported_extension(name: &str) -> bool {
+    gltf::json::extensions::ENABLED_EXTENSIONS.contains(&name)
+}
+
+/// Whether the file name has one of the `GLTF_FILE_EXTENSIONS`, ignoring case.
+pub fn has_gltf_extension(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        GLTF_FILE_EXTENSIONS
+            .iter()
+            .any(|known| extension.to_string_lossy().eq_ignore_ascii_case(known))
+    })
+}
+
+/// Why a glTF file could not be imported.
+#[derive(Debug, Clone)]
+pub enum ImportError {
+    /// The glTF file itself could not be read.
+    Io {
+        path: PathBuf,
+        source: Arc<io::Error>,
+    },
+    /// The JSON is not well-formed, or does not have the structure of a glTF document.
+    Json {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// The binary container of a .glb file is malformed.
+    Glb(String),
+    /// The JSON parsed, but refers to objects that do not exist or has invalid values.
+    Invalid(String),
+    /// A buffer or image refers to a file that does not exist.
+    MissingUri {
+        kind: &'static str,
+        index: usize,
+        uri: String,
+        path: PathBuf,
+    },
+    /// Buffer data could not be read or is shorter than declared.
+    Buffer(String),
+    /// The document lists extensions in `extensionsRequired` that cannot be interpreted.
+    UnsupportedExtensions(Vec<String>),
+    /// An image is not a PNG or JPEG file, or is corrupt.
+    ImageDecode(String),
+    /// A .zip archive holds no glTF file, or several of which none was picked.
+    Archive {
+        path: PathBuf,
+        entries: Vec<PathBuf>,
+    },
+    /// Image data could not be read.
+    Image(String),
+}
+
+impl ImportError {
+    fn from_gltf(error: gltf::Error, file: &Path) -> Self {
+        match error {
+            gltf::Error::Io(error) => Self::Io {
+                path: file.to_path_buf(),
+                source: Arc::new(error),
+            },
+            gltf::Error::Deserialize(error) => Self::Json {
+                line: error.line(),
+                column: error.column(),
+                message: error.to_string(),
+            },
+            gltf::Error::Binary(error) => Self::Glb(error.to_string()),
+            gltf::Error::Validation(_) => Self::Invalid(error.to_string()),
+            gltf::Error::Image(_) | gltf::Error::UnsupportedImageEncoding => {
+                Self::ImageDecode(error.to_string())
+            }
+            gltf::Error::Base64(_)
+            | gltf::Error::BufferLength { .. }
+            | gltf::Error::MissingBlob
+            | gltf::Error::ExternalReferenceInSliceImport
+            | gltf::Error::UnsupportedScheme => Self::Buffer(error.to_string()),
+        }
+    }
+
+    /// The process exit code for this kind of error, so that scripts can tell them apart.
+    ///
+    /// Codes 1 and 2 are left for commands that report findings and for other errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Io { .. } => 3,
+            Self::Json { .. } => 4,
+            Self::Glb(_) => 5,
+            Self::Invalid(_) => 6,
+            Self::MissingUri { .. } => 7,
+            Self::Buffer(_) => 8,
+            Self::UnsupportedExtensions(_) => 9,
+            Self::ImageDecode(_) => 10,
+            Self::Archive { .. } => 11,
+            Self::Image(_) => 12,
+        }
+    }
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "could not read {}: {}", path.display(), source),
+            Self::Json {
+                line,
+                column,
+                message,
+            } => {
+                // serde_json appends the position to its own messages.
+                let message = message
+                    .strip_suffix(&format!(" at line {} column {}", line, column))
+                    .unwrap_or(message);
+                write!(
+                    f,
+                    "invalid glTF JSON at line {}, column {}: {}",
+                    line, column, message
+                )
+            }
+            Self::Glb(message) => write!(
+                f,
+                "invalid .glb file: {}; the file may be truncated or not binary glTF",
+                message
+            ),
+            Self::Invalid(message) => write!(f, "{}", message),
+            Self::MissingUri {
+                kind,
+                index,
+                uri,
+                path,
+            } => write!(
+                f,
+                "{} {} refers to `{}`, but {} does not exist; \
+                 copy the file there or correct the URI",
+                kind,
+                index,
+                uri,
+                path.display()
+            ),
+            Self::Buffer(message) => write!(f, "could not load buffer data: {}", message),
+            Self::UnsupportedExtensions(extensions) => write!(
+                f,
+                "the file requires extensions the explorer does not support: {}; \
+                 supported extensions are {}",
+                extensions.join(", "),
+                gltf::json::extensions::ENABLED_EXTENSIONS.join(", ")
+            ),
+            Self::ImageDecode(message) => write!(
+                f,
+                "could not decode an image: {}; only PNG and JPEG images are supported",
+                message
+            ),
+            Self::Archive { path, entries } if entries.is_empty() => {
+                write!(f, "{} contains no .gltf or .glb files", path.display())
+            }
+            Self::Archive { path, entries } => {
+                let entries: Vec<_> = entries
+                    .iter()
+                    .map(|entry| entry.display().to_string())
+                    .collect();
+                write!(
+                    f,
+                    "{} contains several glTF files; open one of {}",
+                    path.display(),
+                    entries.join(", ")
+                )
+            }
+            Self::Image(message) => write!(f, "could not load image data: {}", message),
+        }
+    }
+}
+
+impl Error for ImportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// The buffers and images whose relative URIs name files that do not exist, so that a
+/// missing one can be named instead of reported as a bare I/O error.
+fn missing_uris(document: &gltf::Document, base: &Path) -> Vec<ImportError> {
+    uri_files(document, base)
+        .into_iter()
+        .filter(|(_, _, _, path)| !archive::exists(path))
+        .map(|(kind, index, uri, path)| ImportError::MissingUri {
+            kind,
+            index,
+            uri: uri.to_owned(),
+            path,
+        })
+        .collect()
+}
+
+/// The kind, index and URI of each buffer and image with a relative URI, and the file it
+/// names.
+fn uri_files<'a>(
+    document: &'a gltf::Document,
+    base: &Path,
+) -> Vec<(&'static str, usize, &'a str, PathBuf)> {
+    let buffers = document
+        .buffers()
+        .filter_map(|buffer| match buffer.source() {
+            gltf::buffer::Source::Uri(uri) => Some(("buffer", buffer.index(), uri)),
+            gltf::buffer::Source::Bin => None,
+        });
+    let images = document.images().filter_map(|image| match image.source() {
+        gltf::image::Source::Uri { uri, .. } => Some(("image", image.index(), uri)),
+        gltf::image::Source::View { .. } => None,
+    });
+
+    buffers
+        .chain(images)
+        .filter(|(_, _, uri)| !uri.contains(':') || uri.starts_with("file:"))
+        .map(|(kind, index, uri)| {
+            let path = base.join(
+                uri.trim_start_matches("file://")
+                    .trim_start_matches("file:"),
+            );
+            (kind, index, uri, path)
+        })
+        .collect()
+}
+
+/// Checks that a buffer has at least as many bytes as it declares.
+fn check_length(buffer: &gltf::Buffer<'_>, data: Vec<u8>) -> Result<Vec<u8>, ImportError> {
+    if data.len() < buffer.length() {
+        return Err(ImportError::Buffer(format!(
+            "buffer {} has {} bytes, but {} are declared",
+            buffer.index(),
+            data.len(),
+            buffer.length()
+        )));
+    }
+    Ok(data)
+}
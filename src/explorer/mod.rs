@@ -1,22 +1,60 @@
-use std::sync::Arc;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+};
 
 use iced::{
     executor,
     widget::{
         button::{self, Button},
-        Container, Row, Text,
+        Column, Container, Row, Text,
     },
     Align, Application, Command, Element, Length, Subscription,
 };
-use log::warn;
+use iced_native::futures::channel::oneshot;
+use log::{info, warn};
 
-use super::Args;
+use crate::{
+    analysis::{diff::Change, size::Sizes},
+    document::{has_gltf_extension, Document, ImportError, Progress, GLTF_FILE_EXTENSIONS, STDIN},
+    export,
+    recent_files::RecentFiles,
+    Args,
+};
 
 mod subscriptions;
 mod widgets;
 
+use subscriptions::{import_gltf::Overview, load_data::LoadData};
+use widgets::gltf_node_tree::NodePath;
+
+/// How many closed tabs can be reopened.
+const MAX_CLOSED_TABS: usize = 10;
+
 pub(crate) struct Explorer {
     open_file_btn: button::State,
+    compare_file_btn: button::State,
+    extract_images_btn: button::State,
+    recent_files_btn: button::State,
+    reopen_tab_btn: button::State,
+    reload_btn: button::State,
+    watch_btn: button::State,
+    tab_bar: widgets::tab_bar::State,
+    /// The tabs that were closed, most recently closed last.
+    closed_tabs: Vec<Tab>,
+    recent_files: RecentFiles,
+    recent_files_list: widgets::recent_files::State,
+    /// Whether the recent files are listed below the toolbar.
+    show_recent_files: bool,
+    /// An archive holding several glTF files, and those files, until one is picked.
+    archive_entries: Option<(PathBuf, Vec<PathBuf>)>,
+    archive_entries_list: widgets::archive_entries::State,
+    status: Option<String>,
+    loading: Option<Loading>,
+    /// Where relative URIs of the document given on the command line are resolved.
+    base_dir: Option<PathBuf>,
     state: State,
 }
 
@@ -26,18 +64,29 @@ impl Application for Explorer {
     type Flags = Args;
 
     fn new(flags: Self::Flags) -> (Self, Command<Self::Message>) {
-        let state = flags
-            .file
-            .map(|file| {
-                State::ExploringDocument(
-                    subscriptions::import_gltf::Document::import(file).unwrap(),
-                    Exploration::default(),
-                )
-            })
-            .unwrap_or(State::Initial);
+        let state = match flags.file {
+            Some(path) => State::ImportingInitialDocument(path),
+            None => State::Initial,
+        };
         (
             Self {
                 open_file_btn: button::State::new(),
+                compare_file_btn: button::State::new(),
+                extract_images_btn: button::State::new(),
+                recent_files_btn: button::State::new(),
+                reopen_tab_btn: button::State::new(),
+                reload_btn: button::State::new(),
+                watch_btn: button::State::new(),
+                tab_bar: Default::default(),
+                closed_tabs: Vec::new(),
+                recent_files: RecentFiles::load(),
+                recent_files_list: Default::default(),
+                show_recent_files: false,
+                archive_entries: None,
+                archive_entries_list: Default::default(),
+                status: None,
+                loading: None,
+                base_dir: flags.base_dir,
                 state,
             },
             Command::none(),
@@ -48,9 +97,19 @@ impl Application for Explorer {
         let app_name = "glTF Explorer";
 
         match &self.state {
-            State::Initial | State::ChoosingInitialDocument => String::from(app_name),
-            State::ExploringDocument(document, _) | State::ChoosingNewDocument(document, _) => {
-                let document_name = document
+            State::Initial
+            | State::ChoosingInitialDocument
+            | State::ImportingInitialDocument(_)
+            | State::ImportingDocument(_) => String::from(app_name),
+            State::ExploringDocument(workspace)
+            | State::ChoosingNewDocument(workspace)
+            | State::ImportingNewDocument(workspace, _)
+            | State::ReloadingDocument(workspace, _)
+            | State::ChoosingComparisonDocument(workspace)
+            | State::ChoosingExportDirectory(workspace, _) => {
+                let document_name = workspace
+                    .active()
+                    .document
                     .path
                     .file_name()
                     .unwrap_or("<unnamed file>".as_ref())
@@ -65,76 +124,522 @@ impl Application for Explorer {
         match message {
             Message::OpenFileDialog => match &self.state {
                 State::Initial => self.state = State::ChoosingInitialDocument,
-                State::ExploringDocument(document, exploration) => {
-                    self.state = State::ChoosingNewDocument(document.clone(), exploration.clone())
+                State::ExploringDocument(workspace) => {
+                    self.state = State::ChoosingNewDocument(workspace.clone())
                 }
-                State::ChoosingInitialDocument | State::ChoosingNewDocument(_, _) => {}
+                State::ChoosingInitialDocument
+                | State::ImportingInitialDocument(_)
+                | State::ImportingDocument(_)
+                | State::ChoosingNewDocument(_)
+                | State::ImportingNewDocument(_, _)
+                | State::ReloadingDocument(_, _)
+                | State::ChoosingComparisonDocument(_)
+                | State::ChoosingExportDirectory(_, _) => {}
             },
-            Message::DocumentOpenProgress(progress) => match self.state {
-                State::Initial | State::ExploringDocument(_, _) => {}
-                State::ChoosingInitialDocument | State::ChoosingNewDocument(_, _) => {
-                    use subscriptions::import_gltf::PickAndImport;
-                    match progress {
-                        PickAndImport::NoFileSelected => match &self.state {
-                            State::Initial | State::ExploringDocument(_, _) => {}
-                            State::ChoosingInitialDocument => self.state = State::Initial,
-                            State::ChoosingNewDocument(document, exploration) => {
-                                self.state =
-                                    State::ExploringDocument(document.clone(), exploration.clone())
-                            }
-                        },
-                        PickAndImport::Finished(document) => {
-                            self.state =
-                                State::ExploringDocument(document.clone(), Exploration::default())
+            Message::OpenComparisonDialog => {
+                if let State::ExploringDocument(workspace) = &self.state {
+                    self.state = State::ChoosingComparisonDocument(workspace.clone())
+                }
+            }
+            Message::OpenExportDialog(export) => {
+                if let State::ExploringDocument(workspace) = &self.state {
+                    self.state = State::ChoosingExportDirectory(workspace.clone(), export)
+                }
+            }
+            Message::ExportDirectoryPicked(picked) => {
+                if let State::ChoosingExportDirectory(workspace, export) = &self.state {
+                    use subscriptions::pick_directory::PickDirectory;
+                    let export = *export;
+                    let document = workspace.active().document.clone();
+                    self.state = State::ExploringDocument(workspace.clone());
+                    match picked {
+                        PickDirectory::NoDirectorySelected => {}
+                        PickDirectory::Selected(dir) => {
+                            self.status =
+                                Some(format!("Exporting {} to {}...", export, dir.display()));
+                            return Command::perform(
+                                export_in_background(document, export, dir.clone()),
+                                move |written| {
+                                    Message::ExportFinished(export, dir.clone(), written)
+                                },
+                            );
+                        }
+                        PickDirectory::Errored(error) => {
+                            warn!("Could not pick a directory: {}", error)
+                        }
+                    }
+                }
+            }
+            Message::ExportFinished(export, dir, written) => match written {
+                Ok(written) => {
+                    let status = format!("Exported {} file(s) to {}", written.len(), dir.display());
+                    info!("{}", status);
+                    self.status = Some(status);
+                }
+                Err(error) => {
+                    warn!("Could not export {}: {}", export, error);
+                    self.status = Some(format!("Could not export {}: {}", export, error));
+                }
+            },
+            Message::QueryChanged(expression) => {
+                if let State::ExploringDocument(workspace) = &mut self.state {
+                    workspace
+                        .active_mut()
+                        .exploration
+                        .query
+                        .set_expression(expression);
+                }
+            }
+            Message::RunQuery => {
+                if let State::ExploringDocument(workspace) = &mut self.state {
+                    let tab = workspace.active_mut();
+                    tab.exploration.query.run(&tab.document.document);
+                }
+            }
+            Message::SelectNode(path) => {
+                if let State::ExploringDocument(workspace) = &mut self.state {
+                    workspace
+                        .active_mut()
+                        .exploration
+                        .gltf_node_tree
+                        .select(path);
+                }
+            }
+            Message::ToggleNode(path) => {
+                if let State::ExploringDocument(workspace) = &mut self.state {
+                    workspace
+                        .active_mut()
+                        .exploration
+                        .gltf_node_tree
+                        .toggle(path);
+                }
+            }
+            Message::CloseComparison => {
+                if let State::ExploringDocument(workspace) = &mut self.state {
+                    workspace.active_mut().exploration.comparison = None;
+                }
+            }
+            Message::SelectTab(index) => {
+                if let State::ExploringDocument(workspace) = &mut self.state {
+                    if index < workspace.tabs.len() {
+                        workspace.active = index;
+                    }
+                }
+            }
+            Message::CloseTab(index) => {
+                if let State::ExploringDocument(workspace) = &mut self.state {
+                    if let Some(tab) = workspace.close(index) {
+                        if self.closed_tabs.len() == MAX_CLOSED_TABS {
+                            self.closed_tabs.remove(0);
                         }
-                        PickAndImport::Errored(error) => {
-                            warn!("Could not open glTF document: {}", error);
-                            match &self.state {
-                                State::Initial | State::ExploringDocument(_, _) => {}
-                                State::ChoosingInitialDocument => self.state = State::Initial,
-                                State::ChoosingNewDocument(document, exploration) => {
-                                    self.state = State::ExploringDocument(
-                                        document.clone(),
-                                        exploration.clone(),
-                                    )
+                        self.closed_tabs.push(tab);
+                    }
+                    if workspace.tabs.is_empty() {
+                        self.state = State::Initial;
+                    }
+                }
+            }
+            Message::ReopenClosedTab => match &mut self.state {
+                State::Initial => {
+                    if let Some(tab) = self.closed_tabs.pop() {
+                        self.state = State::ExploringDocument(Workspace::new(tab));
+                    }
+                }
+                State::ExploringDocument(workspace) => {
+                    if let Some(tab) = self.closed_tabs.pop() {
+                        workspace.open(tab);
+                    }
+                }
+                State::ChoosingInitialDocument
+                | State::ImportingInitialDocument(_)
+                | State::ImportingDocument(_)
+                | State::ChoosingNewDocument(_)
+                | State::ImportingNewDocument(_, _)
+                | State::ReloadingDocument(_, _)
+                | State::ChoosingComparisonDocument(_)
+                | State::ChoosingExportDirectory(_, _) => {}
+            },
+            Message::DocumentOpenProgress(progress) => {
+                use subscriptions::import_gltf::PickAndImport;
+                match progress {
+                    PickAndImport::Started(path) => self.loading = Some(Loading::new(path)),
+                    PickAndImport::Progress(progress) => {
+                        if let Some(loading) = &mut self.loading {
+                            loading.progress = Some(progress);
+                        }
+                    }
+                    PickAndImport::NoFileSelected => self.stop_importing(),
+                    PickAndImport::DialogErrored(error) => {
+                        warn!("Could not pick a file: {}", error);
+                        self.stop_importing();
+                    }
+                    PickAndImport::Errored(ImportError::Archive { path, entries })
+                        if !entries.is_empty()
+                            && !matches!(self.state, State::ChoosingComparisonDocument(_)) =>
+                    {
+                        self.status = Some(format!(
+                            "{} contains several glTF files; pick one to open",
+                            path.display()
+                        ));
+                        self.archive_entries = Some((path, entries));
+                        self.stop_importing();
+                    }
+                    PickAndImport::Errored(error) => {
+                        let status = match self.state {
+                            State::ChoosingComparisonDocument(_) => {
+                                format!("Could not open glTF document for comparison: {}", error)
+                            }
+                            State::ReloadingDocument(_, _) => {
+                                format!("Could not reload glTF document: {}", error)
+                            }
+                            _ => format!("Could not open glTF document: {}", error),
+                        };
+                        warn!("{}", status);
+                        self.status = Some(status);
+                        self.stop_importing();
+                    }
+                    PickAndImport::Finished(other, overview) => {
+                        self.loading = None;
+                        match &self.state {
+                            State::Initial
+                            | State::ExploringDocument(_)
+                            | State::ChoosingExportDirectory(_, _) => {}
+                            State::ChoosingComparisonDocument(workspace) => {
+                                let mut workspace = workspace.clone();
+                                let tab = workspace.active_mut();
+                                tab.exploration.comparison =
+                                    Some(Comparison::new(&tab.document, &other));
+                                self.state = State::ExploringDocument(workspace);
+                            }
+                            State::ChoosingInitialDocument
+                            | State::ImportingInitialDocument(_)
+                            | State::ImportingDocument(_) => {
+                                self.opened(&other);
+                                self.state = State::ExploringDocument(Workspace::new(Tab::new(
+                                    other, overview,
+                                )))
+                            }
+                            State::ChoosingNewDocument(workspace)
+                            | State::ImportingNewDocument(workspace, _) => {
+                                let mut workspace = workspace.clone();
+                                self.opened(&other);
+                                workspace.open(Tab::new(other, overview));
+                                self.state = State::ExploringDocument(workspace)
+                            }
+                            State::ReloadingDocument(workspace, indices) => {
+                                let mut workspace = workspace.clone();
+                                let indices = indices.clone();
+                                self.report_failures(&other);
+                                // Other tabs on the same file share the new version, the
+                                // rest are imported next.
+                                let (same_file, rest): (Vec<usize>, Vec<usize>) =
+                                    indices[1..].iter().partition(|&&index| {
+                                        let document = &workspace.tabs[index].document;
+                                        document.path == other.path
+                                            && document.base_dir() == other.base_dir()
+                                    });
+                                for &index in indices[..1].iter().chain(&same_file) {
+                                    workspace.tabs[index].reload(other.clone(), overview.clone());
+                                }
+                                self.state = if rest.is_empty() {
+                                    State::ExploringDocument(workspace)
+                                } else {
+                                    State::ReloadingDocument(workspace, rest)
                                 }
                             }
                         }
                     }
                 }
-            },
+            }
+            Message::DataLoading(loading) => {
+                if let Some(workspace) = self.state.workspace_mut() {
+                    let (document, progress, sizes) = match loading {
+                        LoadData::Progress(document, progress) => (document, Some(progress), None),
+                        LoadData::Finished(document, sizes) => (document, None, Some(sizes)),
+                    };
+                    for tab in &mut workspace.tabs {
+                        if Arc::ptr_eq(&tab.document, &document) {
+                            tab.data_progress = progress;
+                            tab.sizes = sizes.clone();
+                        }
+                    }
+                    if sizes.is_some() && !document.failures().is_empty() {
+                        self.report_failures(&document);
+                    }
+                }
+            }
+            Message::FileDropped(path) => self.open_file(path),
+            Message::ToggleRecentFiles => self.show_recent_files = !self.show_recent_files,
+            Message::OpenArchiveEntry(index) => {
+                let entry = self.archive_entries.take().and_then(|(_, mut entries)| {
+                    (index < entries.len()).then(|| entries.swap_remove(index))
+                });
+                if let Some(entry) = entry {
+                    self.open_file(entry);
+                }
+            }
+            Message::OpenRecentFile(index) => {
+                if let Some(entry) = self.recent_files.entries().get(index) {
+                    let path = entry.path.clone();
+                    self.open_file(path);
+                }
+            }
+            Message::ReloadDocument => {
+                if let State::ExploringDocument(workspace) = &self.state {
+                    self.reload(vec![workspace.active]);
+                }
+            }
+            Message::FileChanged(path) => {
+                if let State::ExploringDocument(workspace) = &self.state {
+                    let changed: Vec<usize> = (0..workspace.tabs.len())
+                        .filter(|&index| {
+                            let tab = &workspace.tabs[index];
+                            tab.watching
+                                && (tab.document.path == path
+                                    || tab.document.dependencies().contains(&path))
+                        })
+                        .collect();
+                    if !changed.is_empty() {
+                        info!("{} changed, reloading", path.display());
+                        self.reload(changed);
+                    }
+                }
+            }
+            Message::ToggleWatch => {
+                if let State::ExploringDocument(workspace) = &mut self.state {
+                    let tab = workspace.active_mut();
+                    tab.watching = !tab.watching;
+                }
+            }
+            Message::CancelImport => {
+                if let Some(loading) = &self.loading {
+                    info!("Cancelled opening {}", loading.path.display());
+                }
+                self.stop_importing();
+            }
         }
 
         Command::none()
     }
 
     fn view<'a>(&'a mut self) -> Element<'a, Self::Message> {
+        if let Some(loading) = &mut self.loading {
+            let file_name = loading
+                .path
+                .file_name()
+                .unwrap_or("<unnamed file>".as_ref())
+                .to_string_lossy();
+            let progress = match &loading.progress {
+                Some(progress) => progress.to_string(),
+                None => String::from("reading file"),
+            };
+
+            let column = Column::new()
+                .align_items(Align::Center)
+                .push(Text::new(format!("Opening {}", file_name)))
+                .push(Text::new(progress))
+                .push(
+                    Button::new(&mut loading.cancel_btn, Text::new("Cancel"))
+                        .on_press(Message::CancelImport),
+                );
+
+            return Container::new(column)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_x(Align::Center)
+                .align_y(Align::Center)
+                .into();
+        }
+
         let mut open_document_button =
             Button::new(&mut self.open_file_btn, Text::new("Open glTF File"));
-        if matches!(self.state, State::Initial | State::ExploringDocument(_, _)) {
+        let mut reopen_tab_button =
+            Button::new(&mut self.reopen_tab_btn, Text::new("Reopen closed tab"));
+        let idle = matches!(self.state, State::Initial | State::ExploringDocument(_));
+        if idle {
             open_document_button = open_document_button
                 .on_press(Message::OpenFileDialog)
                 .into();
+            if !self.closed_tabs.is_empty() {
+                reopen_tab_button = reopen_tab_button.on_press(Message::ReopenClosedTab);
+            }
         }
 
-        if let State::ExploringDocument(document, exploration)
-        | State::ChoosingNewDocument(document, exploration) = &mut self.state
+        if let State::ExploringDocument(workspace)
+        | State::ChoosingNewDocument(workspace)
+        | State::ImportingNewDocument(workspace, _)
+        | State::ReloadingDocument(workspace, _)
+        | State::ChoosingComparisonDocument(workspace)
+        | State::ChoosingExportDirectory(workspace, _) = &mut self.state
         {
-            Row::new()
-                .push(
-                    Row::new()
-                        .push(widgets::document_statistics::stats(
-                            &document.document,
-                            &mut exploration.document_statistics,
-                        ))
-                        .push(widgets::gltf_node_tree::tree(
-                            &document.document,
-                            &mut exploration.gltf_node_tree,
+            let names = workspace
+                .tabs
+                .iter()
+                .map(|tab| {
+                    tab.document
+                        .path
+                        .file_name()
+                        .unwrap_or("<unnamed file>".as_ref())
+                        .to_string_lossy()
+                        .into_owned()
+                })
+                .collect();
+            let tab_bar = widgets::tab_bar::tab_bar(
+                names,
+                workspace.active,
+                &mut self.tab_bar,
+                Message::SelectTab,
+                Message::CloseTab,
+            )
+            .push(reopen_tab_button);
+            let Tab {
+                document,
+                overview,
+                sizes,
+                data_progress,
+                exploration,
+                watching,
+            } = &mut workspace.tabs[workspace.active];
+
+            let mut compare_document_button =
+                Button::new(&mut self.compare_file_btn, Text::new("Compare with..."));
+            if exploration.comparison.is_none() {
+                compare_document_button =
+                    compare_document_button.on_press(Message::OpenComparisonDialog);
+            }
+
+            let mut extract_images_button =
+                Button::new(&mut self.extract_images_btn, Text::new("Extract images..."));
+            if document.document.images().next().is_some() {
+                extract_images_button =
+                    extract_images_button.on_press(Message::OpenExportDialog(Export::Images));
+            }
+
+            let mut panels = Row::new()
+                .push(widgets::document_statistics::stats(
+                    document,
+                    &overview.statistics,
+                    &overview.extensions,
+                    &mut exploration.document_statistics,
+                ))
+                .push(widgets::size_breakdown::sizes(
+                    sizes.as_deref(),
+                    *data_progress,
+                    &mut exploration.size_breakdown,
+                ))
+                .push(widgets::gltf_node_tree::tree(
+                    &document.document,
+                    &mut exploration.gltf_node_tree,
+                    Message::SelectNode,
+                    Message::ToggleNode,
+                ))
+                .push(widgets::accessor_list::accessors(
+                    &document.document,
+                    &mut exploration.accessor_list,
+                    |index| Message::OpenExportDialog(Export::AccessorCsv(index)),
+                ))
+                .push(widgets::query::query(
+                    &mut exploration.query,
+                    Message::QueryChanged,
+                    Message::RunQuery,
+                ));
+
+            if let Some(comparison) = &mut exploration.comparison {
+                let compared_name = comparison
+                    .path
+                    .file_name()
+                    .unwrap_or("<unnamed file>".as_ref())
+                    .to_string_lossy();
+
+                panels = panels.push(
+                    Column::new()
+                        .push(Text::new(format!("Changes in {}", compared_name)))
+                        .push(
+                            Button::new(&mut comparison.close_btn, Text::new("Close comparison"))
+                                .on_press(Message::CloseComparison),
+                        )
+                        .push(widgets::document_diff::changes(
+                            &comparison.changes,
+                            &mut comparison.document_diff,
                         )),
-                )
-                .into()
+                );
+            }
+
+            let mut reload_button = Button::new(&mut self.reload_btn, Text::new("Reload"));
+            let watch_label = if *watching {
+                "Stop watching"
+            } else {
+                "Watch for changes"
+            };
+            let mut watch_button = Button::new(&mut self.watch_btn, Text::new(watch_label));
+            if idle && document.path != Path::new(STDIN) {
+                reload_button = reload_button.on_press(Message::ReloadDocument);
+                watch_button = watch_button.on_press(Message::ToggleWatch);
+            }
+
+            let mut recent_files_button =
+                Button::new(&mut self.recent_files_btn, Text::new("Recent files"));
+            if !self.recent_files.entries().is_empty() {
+                recent_files_button = recent_files_button.on_press(Message::ToggleRecentFiles);
+            }
+
+            let mut toolbar = Row::new()
+                .push(open_document_button)
+                .push(recent_files_button)
+                .push(reload_button)
+                .push(watch_button)
+                .push(compare_document_button)
+                .push(extract_images_button);
+            if let Some(status) = &self.status {
+                toolbar = toolbar.push(Text::new(status.as_str()));
+            }
+
+            let mut explorer = Column::new().push(toolbar).push(tab_bar);
+            if self.show_recent_files {
+                explorer = explorer.push(widgets::recent_files::recent_files(
+                    &self.recent_files,
+                    &mut self.recent_files_list,
+                    Message::OpenRecentFile,
+                ));
+            }
+            if let Some((archive, entries)) = &self.archive_entries {
+                explorer = explorer.push(widgets::archive_entries::archive_entries(
+                    archive,
+                    entries,
+                    &mut self.archive_entries_list,
+                    Message::OpenArchiveEntry,
+                ));
+            }
+            explorer.push(panels).into()
         } else {
-            Container::new(open_document_button)
+            let mut initial = Column::new()
+                .align_items(Align::Center)
+                .push(open_document_button);
+            if let Some(status) = &self.status {
+                initial = initial.push(Text::new(status.as_str()));
+            }
+            if !self.closed_tabs.is_empty() {
+                initial = initial.push(reopen_tab_button);
+            }
+            if let Some((archive, entries)) = &self.archive_entries {
+                initial = initial.push(widgets::archive_entries::archive_entries(
+                    archive,
+                    entries,
+                    &mut self.archive_entries_list,
+                    Message::OpenArchiveEntry,
+                ));
+            }
+            if !self.recent_files.entries().is_empty() {
+                initial = initial.push(Text::new("Recent files")).push(
+                    widgets::recent_files::recent_files(
+                        &self.recent_files,
+                        &mut self.recent_files_list,
+                        Message::OpenRecentFile,
+                    ),
+                );
+            }
+
+            Container::new(initial)
                 .width(Length::Fill)
                 .height(Length::Fill)
                 .align_x(Align::Center)
@@ -144,11 +649,166 @@ impl Application for Explorer {
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        match self.state {
-            State::Initial | State::ExploringDocument(_, _) => Subscription::none(),
-            State::ChoosingInitialDocument | State::ChoosingNewDocument(_, _) => {
+        // Dropped files are only opened in some states, which `update` checks. Other
+        // events are filtered out here, so that they don't cause updates.
+        let file_drops = iced_native::subscription::events_with(|event| match event {
+            iced_native::Event::Window(iced_native::window::Event::FileDropped(path)) => {
+                Some(Message::FileDropped(path))
+            }
+            _ => None,
+        });
+        // Buffers and images are loaded while the document is explored, in any state.
+        let data = match self.state.workspace() {
+            Some(workspace) => {
+                Subscription::batch(workspace.tabs.iter().filter(|tab| tab.sizes.is_none()).map(
+                    |tab| {
+                        subscriptions::load_data::load_data(tab.document.clone())
+                            .map(Message::DataLoading)
+                    },
+                ))
+            }
+            None => Subscription::none(),
+        };
+        let state = match &self.state {
+            State::Initial => Subscription::none(),
+            State::ExploringDocument(workspace) => Subscription::batch(
+                workspace
+                    .tabs
+                    .iter()
+                    .filter(|tab| tab.watching && tab.document.path != Path::new(STDIN))
+                    .map(|tab| {
+                        subscriptions::watch_file::watch_file(
+                            tab.document.path.clone(),
+                            tab.document.dependencies(),
+                        )
+                        .map(Message::FileChanged)
+                    }),
+            ),
+            State::ChoosingExportDirectory(_, _) => {
+                subscriptions::pick_directory::pick_directory().map(Message::ExportDirectoryPicked)
+            }
+            State::ChoosingInitialDocument
+            | State::ChoosingNewDocument(_)
+            | State::ChoosingComparisonDocument(_) => {
                 subscriptions::import_gltf::pick_and_import().map(Message::DocumentOpenProgress)
             }
+            State::ImportingInitialDocument(path) => {
+                subscriptions::import_gltf::import(path.clone(), self.base_dir.clone())
+                    .map(Message::DocumentOpenProgress)
+            }
+            State::ImportingDocument(path) | State::ImportingNewDocument(_, path) => {
+                subscriptions::import_gltf::import(path.clone(), None)
+                    .map(Message::DocumentOpenProgress)
+            }
+            State::ReloadingDocument(workspace, indices) => {
+                let document = &workspace.tabs[indices[0]].document;
+                subscriptions::import_gltf::import(
+                    document.path.clone(),
+                    Some(document.base_dir().to_path_buf()),
+                )
+                .map(Message::DocumentOpenProgress)
+            }
+        };
+        Subscription::batch(vec![file_drops, data, state])
+    }
+}
+
+impl Explorer {
+    /// Goes back to what was shown before the file dialog, after nothing was imported.
+    fn stop_importing(&mut self) {
+        self.loading = None;
+        match &self.state {
+            State::Initial | State::ExploringDocument(_) | State::ChoosingExportDirectory(_, _) => {
+            }
+            State::ChoosingInitialDocument
+            | State::ImportingInitialDocument(_)
+            | State::ImportingDocument(_) => self.state = State::Initial,
+            State::ChoosingNewDocument(workspace)
+            | State::ImportingNewDocument(workspace, _)
+            | State::ReloadingDocument(workspace, _)
+            | State::ChoosingComparisonDocument(workspace) => {
+                self.state = State::ExploringDocument(workspace.clone())
+            }
+        }
+    }
+
+    /// Remembers a newly opened document and reports what could not be loaded.
+    fn opened(&mut self, document: &Document) {
+        self.recent_files.add(&document.path);
+        if let Err(error) = self.recent_files.save() {
+            warn!("Could not save the recent files: {}", error);
+        }
+        self.show_recent_files = false;
+        self.archive_entries = None;
+        self.report_failures(document);
+    }
+
+    /// Shows how many buffers and images of a newly imported document could not be
+    /// loaded and which required extensions are not supported, and logs why.
+    fn report_failures(&mut self, document: &Document) {
+        let mut notes = Vec::new();
+        let failures = document.failures();
+        for failure in &failures {
+            warn!("{}: {}", document.path.display(), failure);
+        }
+        if !failures.is_empty() {
+            notes.push(format!(
+                "{} buffer(s) or image(s) could not be loaded",
+                failures.len()
+            ));
+        }
+        if let Err(error) = document.check_extensions() {
+            warn!("{}: {}", document.path.display(), error);
+            notes.push(format!(
+                "requires unsupported extensions: {}",
+                document.unsupported_extensions().join(", ")
+            ));
+        }
+        self.status = if notes.is_empty() {
+            None
+        } else {
+            Some(notes.join("; "))
+        };
+    }
+
+    /// Imports the documents in some tabs again, keeping how they are being explored.
+    fn reload(&mut self, mut indices: Vec<usize>) {
+        if let State::ExploringDocument(workspace) = &self.state {
+            indices.retain(|&index| workspace.tabs[index].document.path != Path::new(STDIN));
+            if !indices.is_empty() {
+                self.state = State::ReloadingDocument(workspace.clone(), indices);
+            }
+        }
+    }
+
+    /// Imports a file dropped on the window or picked from the recent files like one
+    /// picked with the file dialog, unless a dialog is open or something else is being
+    /// opened.
+    fn open_file(&mut self, path: PathBuf) {
+        if !has_gltf_extension(&path) {
+            let status = format!(
+                "Cannot open {}: only .{} files are supported",
+                path.display(),
+                GLTF_FILE_EXTENSIONS.join(", .")
+            );
+            warn!("{}", status);
+            self.status = Some(status);
+            return;
+        }
+
+        match &self.state {
+            State::Initial => self.state = State::ImportingDocument(path),
+            State::ExploringDocument(workspace) => {
+                self.state = State::ImportingNewDocument(workspace.clone(), path)
+            }
+            State::ChoosingInitialDocument
+            | State::ImportingInitialDocument(_)
+            | State::ImportingDocument(_)
+            | State::ChoosingNewDocument(_)
+            | State::ImportingNewDocument(_, _)
+            | State::ReloadingDocument(_, _)
+            | State::ChoosingComparisonDocument(_)
+            | State::ChoosingExportDirectory(_, _) => {}
         }
     }
 }
@@ -156,18 +816,260 @@ impl Application for Explorer {
 #[derive(Clone, Debug)]
 pub enum Message {
     OpenFileDialog,
+    OpenComparisonDialog,
+    CloseComparison,
+    SelectNode(NodePath),
+    /// Collapse or expand a scene or node in the node tree.
+    ToggleNode(NodePath),
+    QueryChanged(String),
+    RunQuery,
+    OpenExportDialog(Export),
+    ExportDirectoryPicked(subscriptions::pick_directory::PickDirectory),
+    /// An export to a directory was written, or failed.
+    ExportFinished(Export, PathBuf, Result<Vec<PathBuf>, String>),
     DocumentOpenProgress(subscriptions::import_gltf::PickAndImport),
+    /// Buffers and images of an open document are being loaded in the background.
+    DataLoading(LoadData),
+    CancelImport,
+    /// A file was dropped on the window.
+    FileDropped(PathBuf),
+    ToggleRecentFiles,
+    OpenRecentFile(usize),
+    /// Open one of the `archive_entries`.
+    OpenArchiveEntry(usize),
+    SelectTab(usize),
+    CloseTab(usize),
+    ReopenClosedTab,
+    ReloadDocument,
+    /// A watched file changed on disk.
+    FileChanged(PathBuf),
+    ToggleWatch,
 }
 
 enum State {
     Initial,
     ChoosingInitialDocument,
-    ExploringDocument(Arc<subscriptions::import_gltf::Document>, Exploration),
-    ChoosingNewDocument(Arc<subscriptions::import_gltf::Document>, Exploration),
+    /// Importing the file given on the command line.
+    ImportingInitialDocument(PathBuf),
+    /// Importing a file dropped on the window or picked from the recent files, before
+    /// any document was opened.
+    ImportingDocument(PathBuf),
+    ExploringDocument(Workspace),
+    /// Choosing a file to open in a new tab.
+    ChoosingNewDocument(Workspace),
+    /// Importing a file dropped on the window or picked from the recent files, to open in
+    /// a new tab.
+    ImportingNewDocument(Workspace, PathBuf),
+    /// Importing the documents in some tabs again, one file at a time. Never empty.
+    ReloadingDocument(Workspace, Vec<usize>),
+    /// Choosing a file to compare the document in the active tab with.
+    ChoosingComparisonDocument(Workspace),
+    ChoosingExportDirectory(Workspace, Export),
+}
+
+impl State {
+    /// The open documents, unless none have been opened yet.
+    fn workspace(&self) -> Option<&Workspace> {
+        match self {
+            State::Initial
+            | State::ChoosingInitialDocument
+            | State::ImportingInitialDocument(_)
+            | State::ImportingDocument(_) => None,
+            State::ExploringDocument(workspace)
+            | State::ChoosingNewDocument(workspace)
+            | State::ImportingNewDocument(workspace, _)
+            | State::ReloadingDocument(workspace, _)
+            | State::ChoosingComparisonDocument(workspace)
+            | State::ChoosingExportDirectory(workspace, _) => Some(workspace),
+        }
+    }
+
+    fn workspace_mut(&mut self) -> Option<&mut Workspace> {
+        match self {
+            State::Initial
+            | State::ChoosingInitialDocument
+            | State::ImportingInitialDocument(_)
+            | State::ImportingDocument(_) => None,
+            State::ExploringDocument(workspace)
+            | State::ChoosingNewDocument(workspace)
+            | State::ImportingNewDocument(workspace, _)
+            | State::ReloadingDocument(workspace, _)
+            | State::ChoosingComparisonDocument(workspace)
+            | State::ChoosingExportDirectory(workspace, _) => Some(workspace),
+        }
+    }
+}
+
+/// Writes an export on a background thread, so that decoding and writing many images
+/// doesn't hold up the explorer.
+async fn export_in_background(
+    document: Arc<Document>,
+    export: Export,
+    dir: PathBuf,
+) -> Result<Vec<PathBuf>, String> {
+    let (sender, receiver) = oneshot::channel();
+    thread::spawn(move || {
+        let written = match export {
+            Export::Images => {
+                export::write_images(&document, 0..document.document.images().count(), &dir)
+            }
+            Export::AccessorCsv(index) => {
+                export::write_accessor_csv(&document, index, true, &dir).map(|path| vec![path])
+            }
+        };
+        let _ = sender.send(written.map_err(|error| error.to_string()));
+    });
+    receiver
+        .await
+        .unwrap_or_else(|_| Err(String::from("the export stopped unexpectedly")))
+}
+
+/// Something to write to a directory picked by the user.
+#[derive(Clone, Copy, Debug)]
+pub enum Export {
+    Images,
+    AccessorCsv(usize),
+}
+
+impl fmt::Display for Export {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Images => f.write_str("images"),
+            Self::AccessorCsv(index) => write!(f, "accessor {}", index),
+        }
+    }
+}
+
+/// The open documents, one per tab.
+#[derive(Clone)]
+struct Workspace {
+    tabs: Vec<Tab>,
+    /// The index of the tab being shown.
+    active: usize,
+}
+
+impl Workspace {
+    fn new(tab: Tab) -> Self {
+        Self {
+            tabs: vec![tab],
+            active: 0,
+        }
+    }
+
+    fn active(&self) -> &Tab {
+        &self.tabs[self.active]
+    }
+
+    fn active_mut(&mut self) -> &mut Tab {
+        &mut self.tabs[self.active]
+    }
+
+    /// Adds a tab and shows it.
+    fn open(&mut self, tab: Tab) {
+        self.tabs.push(tab);
+        self.active = self.tabs.len() - 1;
+    }
+
+    /// Removes a tab, showing the one before it if it was the active one at the end.
+    fn close(&mut self, index: usize) -> Option<Tab> {
+        if index >= self.tabs.len() {
+            return None;
+        }
+        let tab = self.tabs.remove(index);
+        if self.active > index || self.active == self.tabs.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+        Some(tab)
+    }
+}
+
+/// A document and how it is being explored, kept while other tabs are shown.
+#[derive(Clone)]
+struct Tab {
+    document: Arc<Document>,
+    overview: Arc<Overview>,
+    /// The sizes, once the buffers and images have been loaded in the background.
+    sizes: Option<Arc<Sizes>>,
+    /// How far loading the buffers and images got.
+    data_progress: Option<Progress>,
+    exploration: Exploration,
+    /// Whether the document is reloaded when it changes on disk, even while the tab is
+    /// not shown.
+    watching: bool,
+}
+
+impl Tab {
+    fn new(document: Arc<Document>, overview: Arc<Overview>) -> Self {
+        Self {
+            document,
+            overview,
+            sizes: None,
+            data_progress: None,
+            exploration: Exploration::default(),
+            watching: false,
+        }
+    }
+
+    /// Replaces the document with a newly imported version of it.
+    ///
+    /// Scroll positions and the query are kept, and so are the selected and collapsed
+    /// nodes wherever the same node paths still exist. A comparison is dropped, since it
+    /// was made against the previous version.
+    fn reload(&mut self, document: Arc<Document>, overview: Arc<Overview>) {
+        let exploration = &mut self.exploration;
+        exploration.query.reload(&document.document);
+        exploration.gltf_node_tree.reload(&document.document);
+        exploration.comparison = None;
+        self.document = document;
+        self.overview = overview;
+        self.sizes = None;
+        self.data_progress = None;
+    }
 }
 
 #[derive(Clone, Default)]
 struct Exploration {
     document_statistics: widgets::document_statistics::State,
+    size_breakdown: widgets::size_breakdown::State,
     gltf_node_tree: widgets::gltf_node_tree::State,
+    accessor_list: widgets::accessor_list::State,
+    query: widgets::query::State,
+    comparison: Option<Comparison>,
+}
+
+/// A file being imported in the background.
+struct Loading {
+    path: PathBuf,
+    progress: Option<Progress>,
+    cancel_btn: button::State,
+}
+
+impl Loading {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            progress: None,
+            cancel_btn: button::State::new(),
+        }
+    }
+}
+
+/// The changes from the explored document to another one picked by the user.
+#[derive(Clone)]
+struct Comparison {
+    path: PathBuf,
+    changes: Vec<Change>,
+    close_btn: button::State,
+    document_diff: widgets::document_diff::State,
+}
+
+impl Comparison {
+    fn new(document: &Document, other: &Document) -> Self {
+        Self {
+            path: other.path.clone(),
+            changes: crate::analysis::diff::diff(&document.document, &other.document),
+            close_btn: button::State::new(),
+            document_diff: Default::default(),
+        }
+    }
 }
@@ -1,64 +1,141 @@
-use std::{
-    path::{Path, PathBuf},
-    sync::Arc,
+use std::{hash::Hash, path::PathBuf, sync::Arc, thread};
+
+use iced_native::futures::{
+    channel::mpsc,
+    future::{self, BoxFuture},
+    stream::{self, BoxStream},
+    FutureExt, StreamExt,
 };
 
-const GLTF_FILE_EXTENSIONS: &[&str] = &["glb", "gltf"];
+use crate::{
+    analysis::{
+        extensions::{self, Extension},
+        statistics::Statistics,
+    },
+    document::{Document, ImportError, Progress, GLTF_FILE_EXTENSIONS},
+};
 
+/// Asks for a glTF file, then imports it on a background thread.
 pub fn pick_and_import() -> iced::Subscription<PickAndImport> {
-    super::dialog::dialog(native_dialog::OpenSingleFile {
+    let picked = native_dialog::OpenSingleFile {
         dir: None,
         filter: Some(GLTF_FILE_EXTENSIONS),
+    }
+    .create_async()
+    .map(|picked| {
+        picked
+            .map(|path| path.map(PathBuf::from))
+            .map_err(|error| error.to_string())
     })
-    .map(|res| match res {
-        Ok(path) => {
-            if let Some(path) = path {
-                let path = PathBuf::from(path);
-                match gltf::import(&path) {
-                    Ok((document, buffers, images)) => {
-                        PickAndImport::Finished(Arc::new(Document {
-                            path,
-                            document,
-                            buffers,
-                            images,
-                        }))
-                    }
-                    Err(err) => PickAndImport::Errored(err.to_string()),
-                }
-            } else {
-                PickAndImport::NoFileSelected
-            }
-        }
-        Err(err) => PickAndImport::Errored(err.to_string()),
+    .boxed();
+    iced_native::Subscription::from_recipe(ImportRecipe {
+        path: None,
+        base_dir: None,
+        picked,
+    })
+}
+
+/// Imports a glTF file on a background thread, reading standard input if `path` is `-`.
+/// Relative URIs are resolved against `base_dir` if given.
+pub fn import(path: PathBuf, base_dir: Option<PathBuf>) -> iced::Subscription<PickAndImport> {
+    let picked = future::ready(Ok(Some(path.clone()))).boxed();
+    iced_native::Subscription::from_recipe(ImportRecipe {
+        path: Some(path),
+        base_dir,
+        picked,
     })
 }
 
 #[derive(Debug, Clone)]
 pub enum PickAndImport {
     NoFileSelected,
-    Finished(Arc<Document>),
-    Errored(String),
+    DialogErrored(String),
+    Started(PathBuf),
+    Progress(Progress),
+    Finished(Arc<Document>, Arc<Overview>),
+    Errored(ImportError),
 }
 
-#[derive(Debug, Clone)]
-pub struct Document {
-    pub path: PathBuf,
-    pub document: gltf::Document,
-    pub buffers: Vec<gltf::buffer::Data>,
-    pub images: Vec<gltf::image::Data>,
+struct ImportRecipe {
+    /// The file to import when known up front, which tells imports of different files
+    /// apart.
+    path: Option<PathBuf>,
+    base_dir: Option<PathBuf>,
+    picked: BoxFuture<'static, Result<Option<PathBuf>, String>>,
 }
 
-impl Document {
-    pub fn import<P: AsRef<Path>>(file: P) -> Result<Arc<Self>, String> {
-        gltf::import(file.as_ref())
-            .map(|(document, buffers, images)| {
-                Arc::new(Document {
-                    path: file.as_ref().to_path_buf(),
-                    document,
-                    buffers,
-                    images,
-                })
+impl<H, I> iced_native::subscription::Recipe<H, I> for ImportRecipe
+where
+    H: std::hash::Hasher,
+{
+    type Output = PickAndImport;
+
+    fn hash(&self, state: &mut H) {
+        std::any::TypeId::of::<Self>().hash(state);
+        self.path.hash(state);
+        self.base_dir.hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, I>) -> BoxStream<'static, Self::Output> {
+        let base_dir = self.base_dir;
+        self.picked
+            .into_stream()
+            .flat_map(move |picked| match picked {
+                Ok(Some(path)) => import_in_background(path, base_dir.clone()),
+                Ok(None) => stream::once(future::ready(PickAndImport::NoFileSelected)).boxed(),
+                Err(error) => {
+                    stream::once(future::ready(PickAndImport::DialogErrored(error))).boxed()
+                }
             })
-            .map_err(|err| err.to_string())
+            .boxed()
+    }
+}
+
+fn import_in_background(
+    path: PathBuf,
+    base_dir: Option<PathBuf>,
+) -> BoxStream<'static, PickAndImport> {
+    let (sender, receiver) = mpsc::unbounded();
+    let _ = sender.unbounded_send(PickAndImport::Started(path.clone()));
+
+    thread::spawn(move || {
+        // Missing files are recorded in the document, which is worth exploring anyway.
+        let document = match Document::import_arg(&path, base_dir.as_deref()) {
+            Ok(document) => document,
+            Err(error) => {
+                let _ = sender.unbounded_send(PickAndImport::Errored(error));
+                return;
+            }
+        };
+        // The channel is closed once the subscription has been dropped, which is how a
+        // cancelled import is noticed. Buffers and images are loaded afterwards, see
+        // `load_data`, so that the document can be browsed right away.
+        if sender
+            .unbounded_send(PickAndImport::Progress(Progress::Parsed))
+            .is_err()
+        {
+            return;
+        }
+        let overview = Arc::new(Overview::new(&document));
+        let _ = sender.unbounded_send(PickAndImport::Finished(document, overview));
+    });
+
+    receiver.boxed()
+}
+
+/// What the panels show about a document that only depends on its JSON, worked out along
+/// with the import rather than while drawing.
+#[derive(Debug, Clone)]
+pub struct Overview {
+    pub statistics: Statistics,
+    pub extensions: Vec<Extension>,
+}
+
+impl Overview {
+    fn new(document: &Document) -> Self {
+        Self {
+            statistics: Statistics::from_document(&document.document),
+            extensions: extensions::from_document(&document.document),
+        }
     }
 }
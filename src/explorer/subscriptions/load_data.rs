@@ -0,0 +1,62 @@
+use std::{hash::Hash, sync::Arc, thread};
+
+use iced_native::futures::{
+    channel::mpsc,
+    stream::{BoxStream, StreamExt},
+};
+
+use crate::{
+    analysis::size::Sizes,
+    document::{Document, Progress},
+};
+
+/// Loads the buffers and images of an imported document on a background thread and works
+/// out its sizes, while the document is already being explored.
+pub fn load_data(document: Arc<Document>) -> iced::Subscription<LoadData> {
+    iced_native::Subscription::from_recipe(LoadRecipe { document })
+}
+
+#[derive(Debug, Clone)]
+pub enum LoadData {
+    Progress(Arc<Document>, Progress),
+    Finished(Arc<Document>, Arc<Sizes>),
+}
+
+struct LoadRecipe {
+    document: Arc<Document>,
+}
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for LoadRecipe
+where
+    H: std::hash::Hasher,
+{
+    type Output = LoadData;
+
+    fn hash(&self, state: &mut H) {
+        std::any::TypeId::of::<Self>().hash(state);
+        // A reloaded document has the same path, but has to be loaded again.
+        Arc::as_ptr(&self.document).hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, I>) -> BoxStream<'static, Self::Output> {
+        let (sender, receiver) = mpsc::unbounded();
+        let document = self.document;
+
+        thread::spawn(move || {
+            // The channel is closed once the subscription has been dropped. Whatever was
+            // loaded until then is kept in the document, so starting over is cheap.
+            let report = |progress| {
+                sender
+                    .unbounded_send(LoadData::Progress(document.clone(), progress))
+                    .is_ok()
+            };
+            if !document.load_all(report) {
+                return;
+            }
+            let sizes = Arc::new(Sizes::from_document(&document));
+            let _ = sender.unbounded_send(LoadData::Finished(document, sizes));
+        });
+
+        receiver.boxed()
+    }
+}
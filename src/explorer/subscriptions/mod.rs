@@ -1,2 +1,5 @@
 pub mod dialog;
 pub mod import_gltf;
+pub mod load_data;
+pub mod pick_directory;
+pub mod watch_file;
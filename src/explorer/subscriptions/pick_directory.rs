@@ -0,0 +1,16 @@
+use std::path::PathBuf;
+
+pub fn pick_directory() -> iced::Subscription<PickDirectory> {
+    super::dialog::dialog(native_dialog::OpenSingleDir { dir: None }).map(|res| match res {
+        Ok(Some(path)) => PickDirectory::Selected(PathBuf::from(path)),
+        Ok(None) => PickDirectory::NoDirectorySelected,
+        Err(err) => PickDirectory::Errored(err.to_string()),
+    })
+}
+
+#[derive(Debug, Clone)]
+pub enum PickDirectory {
+    NoDirectorySelected,
+    Selected(PathBuf),
+    Errored(String),
+}
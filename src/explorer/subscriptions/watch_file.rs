@@ -0,0 +1,50 @@
+use std::{hash::Hash, iter, path::PathBuf, thread};
+
+use iced_native::futures::{
+    channel::mpsc,
+    stream::{BoxStream, StreamExt},
+};
+
+use crate::watch::{Watcher, POLL_INTERVAL};
+
+/// Produces the path of a file every time it or one of its `dependencies` changes on
+/// disk.
+pub fn watch_file(path: PathBuf, dependencies: Vec<PathBuf>) -> iced::Subscription<PathBuf> {
+    iced_native::Subscription::from_recipe(WatchRecipe { path, dependencies })
+}
+
+struct WatchRecipe {
+    path: PathBuf,
+    dependencies: Vec<PathBuf>,
+}
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for WatchRecipe
+where
+    H: std::hash::Hasher,
+{
+    type Output = PathBuf;
+
+    fn hash(&self, state: &mut H) {
+        std::any::TypeId::of::<Self>().hash(state);
+        self.path.hash(state);
+        self.dependencies.hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, I>) -> BoxStream<'static, Self::Output> {
+        let (sender, receiver) = mpsc::unbounded();
+        let WatchRecipe { path, dependencies } = *self;
+
+        thread::spawn(move || {
+            let mut watcher = Watcher::new(iter::once(path.clone()).chain(dependencies));
+            // The channel is closed once the subscription has been dropped.
+            while !sender.is_closed() {
+                thread::sleep(POLL_INTERVAL);
+                if watcher.changed() {
+                    let _ = sender.unbounded_send(path.clone());
+                }
+            }
+        });
+
+        receiver.boxed()
+    }
+}
@@ -0,0 +1,58 @@
+use iced_native::widget::{
+    button::{self, Button},
+    scrollable::{self, Scrollable},
+    Row, Text,
+};
+
+pub fn accessors<'a, Message, Renderer>(
+    document: &'a gltf::Document,
+    state: &'a mut State,
+    on_export: fn(usize) -> Message,
+) -> Scrollable<'a, Message, Renderer>
+where
+    Renderer: iced_native::widget::scrollable::Renderer
+        + iced_native::widget::text::Renderer
+        + iced_native::widget::button::Renderer
+        + iced_native::widget::row::Renderer
+        + 'a,
+    Message: Clone + 'a,
+{
+    state
+        .export_buttons
+        .resize_with(document.accessors().count(), Default::default);
+
+    let State {
+        scrollable,
+        export_buttons,
+    } = state;
+
+    document.accessors().zip(export_buttons.iter_mut()).fold(
+        Scrollable::new(scrollable),
+        |scrollable, (accessor, export_button)| {
+            let description = format!(
+                "#{} {}{:?} {:?} x{}",
+                accessor.index(),
+                accessor
+                    .name()
+                    .map(|name| format!("{} ", name))
+                    .unwrap_or_default(),
+                accessor.dimensions(),
+                accessor.data_type(),
+                accessor.count()
+            );
+
+            scrollable.push(
+                Row::new().push(Text::new(description)).push(
+                    Button::new(export_button, Text::new("Export CSV"))
+                        .on_press(on_export(accessor.index())),
+                ),
+            )
+        },
+    )
+}
+
+#[derive(Clone, Default)]
+pub struct State {
+    scrollable: scrollable::State,
+    export_buttons: Vec<button::State>,
+}
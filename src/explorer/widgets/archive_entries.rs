@@ -0,0 +1,52 @@
+use std::path::{Path, PathBuf};
+
+use iced_native::widget::{
+    button::{self, Button},
+    scrollable::{self, Scrollable},
+    Text,
+};
+
+/// The glTF files in an archive, to pick the one to open.
+pub fn archive_entries<'a, Message, Renderer>(
+    archive: &Path,
+    entries: &[PathBuf],
+    state: &'a mut State,
+    on_open: fn(usize) -> Message,
+) -> Scrollable<'a, Message, Renderer>
+where
+    Renderer: iced_native::widget::scrollable::Renderer
+        + iced_native::widget::text::Renderer
+        + iced_native::widget::button::Renderer
+        + 'a,
+    Message: Clone + 'a,
+{
+    state
+        .open_buttons
+        .resize_with(entries.len(), Default::default);
+
+    let State {
+        scrollable,
+        open_buttons,
+    } = state;
+
+    entries
+        .iter()
+        .zip(open_buttons.iter_mut())
+        .enumerate()
+        .fold(
+            Scrollable::new(scrollable),
+            |scrollable, (index, (entry, open_button))| {
+                let name = entry.strip_prefix(archive).unwrap_or(entry);
+                scrollable.push(
+                    Button::new(open_button, Text::new(name.display().to_string()))
+                        .on_press(on_open(index)),
+                )
+            },
+        )
+}
+
+#[derive(Clone, Default)]
+pub struct State {
+    scrollable: scrollable::State,
+    open_buttons: Vec<button::State>,
+}
@@ -0,0 +1,30 @@
+use iced_native::widget::{
+    scrollable::{self, Scrollable},
+    Text,
+};
+
+use crate::analysis::diff::Change;
+
+pub fn changes<'a, Message, Renderer>(
+    changes: &'a [Change],
+    state: &'a mut State,
+) -> Scrollable<'a, Message, Renderer>
+where
+    Renderer: iced_native::widget::scrollable::Renderer + iced_native::widget::text::Renderer + 'a,
+    Message: 'a,
+{
+    let scrollable = Scrollable::new(&mut state.scrollable);
+
+    if changes.is_empty() {
+        scrollable.push(Text::new("No structural changes"))
+    } else {
+        changes.iter().fold(scrollable, |scrollable, change| {
+            scrollable.push(Text::new(change.to_string()))
+        })
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct State {
+    scrollable: scrollable::State,
+}
@@ -3,62 +3,49 @@ use iced_native::widget::{
     Text,
 };
 
+use crate::{
+    analysis::{extensions::Extension, statistics::Statistics},
+    document::Document,
+};
+
 pub fn stats<'a, Message, Renderer>(
-    document: &'a gltf::Document,
+    document: &'a Document,
+    statistics: &Statistics,
+    extensions: &[Extension],
     state: &'a mut State,
 ) -> Scrollable<'a, Message, Renderer>
 where
     Renderer: iced_native::widget::scrollable::Renderer + iced_native::widget::text::Renderer + 'a,
     Message: 'a,
 {
-    Scrollable::new(&mut state.scrollable)
-        .push(Text::new(format!(
-            "Accessors: {}",
-            document.accessors().count()
-        )))
-        .push(Text::new(format!(
-            "Animations: {}",
-            document.animations().count()
-        )))
-        .push(Text::new(format!(
-            "Buffers: {}",
-            document.buffers().count()
-        )))
-        .push(Text::new(format!(
-            "Cameras: {}",
-            document.cameras().count()
-        )))
-        .push(Text::new(format!(
-            "Has default scene: {}",
-            document.default_scene().is_some()
-        )))
-        .push(Text::new(format!(
-            "Extensions used: {}",
-            document.extensions_used().count()
-        )))
-        .push(Text::new(format!(
-            "Extensions required: {}",
-            document.extensions_required().count()
-        )))
-        .push(Text::new(format!("Images: {}", document.images().count())))
-        .push(Text::new(format!(
-            "Materials: {}",
-            document.materials().count()
-        )))
-        .push(Text::new(format!("Meshes: {}", document.meshes().count())))
-        .push(Text::new(format!(
-            "Samplers: {}",
-            document.samplers().count()
-        )))
-        .push(Text::new(format!("Skins: {}", document.skins().count())))
-        .push(Text::new(format!(
-            "Textures: {}",
-            document.textures().count()
-        )))
-        .push(Text::new(format!(
-            "Buffer views: {}",
-            document.views().count()
-        )))
+    let scrollable = statistics.rows().into_iter().fold(
+        Scrollable::new(&mut state.scrollable),
+        |scrollable, (label, value)| scrollable.push(Text::new(format!("{}: {}", label, value))),
+    );
+
+    let failures = document.failures();
+    let scrollable = if failures.is_empty() {
+        scrollable
+    } else {
+        failures.into_iter().fold(
+            scrollable.push(Text::new("Could not load:")),
+            |scrollable, failure| scrollable.push(Text::new(format!("  {}", failure))),
+        )
+    };
+
+    extensions.iter().fold(scrollable, |scrollable, extension| {
+        extension.objects.iter().fold(
+            scrollable.push(Text::new(format!(
+                "{} ({})",
+                extension.name,
+                extension.status()
+            ))),
+            |scrollable, object| {
+                let object = if object.is_empty() { "root" } else { object };
+                scrollable.push(Text::new(format!("  {}", object)))
+            },
+        )
+    })
 }
 
 #[derive(Clone, Default)]
@@ -1,30 +1,81 @@
+use std::collections::{HashMap, HashSet};
+
 use iced_graphics::{canvas, Rectangle};
 use iced_native::{
     mouse,
     widget::{
+        button::{self, Button},
         scrollable::{self, Scrollable},
-        Text,
+        Row, Text,
     },
+    Element,
 };
 
 use super::{
     canvas_background::CanvasBackground,
-    tree::{Tree, TreeTraverser},
+    tree::{self, Tree, TreeTraverser},
 };
 
 pub fn tree<'a, Message, B>(
     document: &'a gltf::Document,
     state: &'a mut State,
+    on_select: fn(NodePath) -> Message,
+    on_toggle: fn(NodePath) -> Message,
 ) -> Scrollable<'a, Message, iced_graphics::Renderer<B>>
 where
     B: iced_graphics::Backend + iced_graphics::backend::Text + 'a,
-    Message: 'a,
+    Message: Clone + 'a,
 {
-    Scrollable::new(&mut state.scrollable).push(CanvasBackground::new(
+    let State {
+        scrollable,
+        selected,
+        collapsed,
+        buttons,
+    } = state;
+    let selected = selected.as_ref();
+    let collapsed = &*collapsed;
+
+    let rows = tree::items(GltfTraverser::new(document, collapsed)).count();
+    buttons.resize_with(rows, Default::default);
+    let mut buttons = buttons.iter_mut();
+
+    Scrollable::new(scrollable).push(CanvasBackground::new(
         CanvasProgram::new(),
-        Tree::new(GltfTraverser::new(document), |node_info| {
-            Text::new(node_info.name.unwrap_or("<unnamed node>"))
-        }),
+        Tree::new(
+            GltfTraverser::new(document, collapsed),
+            move |node_info| -> Element<'a, Message, iced_graphics::Renderer<B>> {
+                let name = node_info.name.unwrap_or("<unnamed node>");
+                let label = if selected == Some(&node_info.path) {
+                    format!("> {}", name)
+                } else {
+                    name.to_owned()
+                };
+
+                let (toggle_button, select_button) = match buttons.next() {
+                    Some((toggle_button, select_button)) => (toggle_button, select_button),
+                    None => return Text::new(label).into(),
+                };
+                let marker = if !node_info.has_children {
+                    " "
+                } else if collapsed.contains(&node_info.path) {
+                    "+"
+                } else {
+                    "-"
+                };
+                let mut toggle = Button::new(toggle_button, Text::new(marker));
+                if node_info.has_children {
+                    toggle = toggle.on_press(on_toggle(node_info.path.clone()));
+                }
+
+                Row::new()
+                    .push(toggle)
+                    .push(
+                        Button::new(select_button, Text::new(label))
+                            .on_press(on_select(node_info.path.clone())),
+                    )
+                    .into()
+            },
+        ),
     ))
 }
 
@@ -57,49 +108,118 @@ impl<Message> canvas::Program<Message> for CanvasProgram {
     }
 }
 
+/// Where a scene or node is in the tree, from the scene down to it.
+///
+/// Unlike indices, names usually survive edits to the file, so a path still leads to
+/// the same node after the document is reloaded.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NodePath(Vec<Segment>);
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Segment {
+    /// The name, and how many earlier siblings have the same name, like
+    /// `analysis::diff::node_paths` numbers them.
+    Named(String, usize),
+    /// The index of an unnamed scene or node, which cannot be mistaken for a name.
+    Unnamed(usize),
+}
+
+/// The names of the siblings seen so far, to tell apart siblings with the same name.
+#[derive(Debug, Default)]
+struct Siblings(HashMap<String, usize>);
+
+impl Siblings {
+    fn segment(&mut self, index: usize, name: Option<&str>) -> Segment {
+        match name {
+            Some(name) => {
+                let earlier = self.0.entry(name.to_owned()).or_insert(0);
+                *earlier += 1;
+                Segment::Named(name.to_owned(), *earlier - 1)
+            }
+            None => Segment::Unnamed(index),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct GltfTraverser<'a> {
     default_scene: Option<gltf::Scene<'a>>,
-    scenes: (IterState, gltf::iter::Scenes<'a>),
-    scene_nodes: Option<gltf::scene::iter::Nodes<'a>>,
-    node_stack: Vec<(IterState, gltf::scene::iter::Children<'a>)>,
+    scenes: (IterState, gltf::iter::Scenes<'a>, Siblings),
+    scene_nodes: Option<(gltf::scene::iter::Nodes<'a>, Siblings)>,
+    node_stack: Vec<(IterState, gltf::scene::iter::Children<'a>, Siblings)>,
+    /// The path of the current scene or node.
+    path: NodePath,
+    /// The scenes and nodes whose children are skipped.
+    collapsed: &'a HashSet<NodePath>,
 }
 
 impl<'a> GltfTraverser<'a> {
-    pub fn new(document: &'a gltf::Document) -> Self {
+    pub fn new(document: &'a gltf::Document, collapsed: &'a HashSet<NodePath>) -> Self {
         Self {
             default_scene: document.default_scene(),
-            scenes: (IterState::Initial, document.scenes()),
+            scenes: (IterState::Initial, document.scenes(), Siblings::default()),
             scene_nodes: None,
             node_stack: Vec::with_capacity(8),
+            path: NodePath::default(),
+            collapsed,
         }
     }
+
+    fn enter_scene(&mut self, scene: gltf::Scene<'a>, segment: Segment) -> NodeInfo<'a> {
+        self.scene_nodes = Some((scene.nodes(), Siblings::default()));
+        self.path.0.clear();
+        self.path.0.push(segment);
+        NodeInfo::from_scene(scene, self.default_scene.as_ref(), self.path.clone())
+    }
+
+    fn enter_node(&mut self, node: gltf::Node<'a>, segment: Segment) -> NodeInfo<'a> {
+        self.node_stack
+            .push((IterState::Initial, node.children(), Siblings::default()));
+        self.path.0.push(segment);
+        NodeInfo::from_node(node, self.path.clone())
+    }
+
+    fn leave_node(&mut self) {
+        self.node_stack.pop();
+        self.path.0.pop();
+    }
+
+    fn is_collapsed(&self) -> bool {
+        self.collapsed.contains(&self.path)
+    }
 }
 
 impl<'a> TreeTraverser for GltfTraverser<'a> {
     type Item = NodeInfo<'a>;
 
     fn first_child(&mut self) -> Option<Self::Item> {
-        if let Some((iter_state, children)) = self.node_stack.last_mut() {
+        let collapsed = self.is_collapsed();
+        if let Some((iter_state, children, siblings)) = self.node_stack.last_mut() {
             if *iter_state == IterState::Initial {
                 *iter_state = IterState::Used;
+                if collapsed {
+                    return None;
+                }
                 if let Some(node) = children.next() {
-                    self.node_stack.push((IterState::Initial, node.children()));
-                    return Some(NodeInfo::from_node(node));
+                    let segment = siblings.segment(node.index(), node.name());
+                    return Some(self.enter_node(node, segment));
                 }
             }
-        } else if let Some(nodes) = self.scene_nodes.as_mut() {
+        } else if let Some((nodes, siblings)) = self.scene_nodes.as_mut() {
+            if collapsed {
+                return None;
+            }
             if let Some(node) = nodes.next() {
-                self.node_stack.push((IterState::Initial, node.children()));
-                return Some(NodeInfo::from_node(node));
+                let segment = siblings.segment(node.index(), node.name());
+                return Some(self.enter_node(node, segment));
             }
         } else {
-            let (iter_state, scenes) = &mut self.scenes;
+            let (iter_state, scenes, siblings) = &mut self.scenes;
             if *iter_state == IterState::Initial {
                 *iter_state = IterState::Used;
                 if let Some(scene) = scenes.next() {
-                    self.scene_nodes = Some(scene.nodes());
-                    return Some(NodeInfo::from_scene(scene, self.default_scene.as_ref()));
+                    let segment = siblings.segment(scene.index(), scene.name());
+                    return Some(self.enter_scene(scene, segment));
                 }
             }
         }
@@ -110,27 +230,27 @@ impl<'a> TreeTraverser for GltfTraverser<'a> {
     fn next_sibling(&mut self) -> Option<Self::Item> {
         let node_stack_len = self.node_stack.len();
         if node_stack_len >= 2 {
-            if let Some((_, children)) = self.node_stack.get_mut(node_stack_len - 2) {
+            if let Some((_, children, siblings)) = self.node_stack.get_mut(node_stack_len - 2) {
                 if let Some(child) = children.next() {
-                    self.node_stack.pop();
-                    self.node_stack.push((IterState::Initial, child.children()));
-                    return Some(NodeInfo::from_node(child));
+                    let segment = siblings.segment(child.index(), child.name());
+                    self.leave_node();
+                    return Some(self.enter_node(child, segment));
                 }
             }
         } else if node_stack_len == 1 {
-            if let Some(scene_nodes) = self.scene_nodes.as_mut() {
+            if let Some((scene_nodes, siblings)) = self.scene_nodes.as_mut() {
                 if let Some(node) = scene_nodes.next() {
-                    self.node_stack.pop();
-                    self.node_stack.push((IterState::Initial, node.children()));
-                    return Some(NodeInfo::from_node(node));
+                    let segment = siblings.segment(node.index(), node.name());
+                    self.leave_node();
+                    return Some(self.enter_node(node, segment));
                 }
             }
         } else {
-            let (iter_state, scenes) = &mut self.scenes;
+            let (iter_state, scenes, siblings) = &mut self.scenes;
             if *iter_state == IterState::Used {
                 if let Some(scene) = scenes.next() {
-                    self.scene_nodes = Some(scene.nodes());
-                    return Some(NodeInfo::from_scene(scene, self.default_scene.as_ref()));
+                    let segment = siblings.segment(scene.index(), scene.name());
+                    return Some(self.enter_scene(scene, segment));
                 }
             }
         }
@@ -143,7 +263,7 @@ impl<'a> TreeTraverser for GltfTraverser<'a> {
         loop {
             if !self.node_stack.is_empty() {
                 levels += 1;
-                self.node_stack.pop();
+                self.leave_node();
                 if let Some(node) = self.next_sibling() {
                     break Some((node, levels));
                 }
@@ -164,6 +284,8 @@ pub struct NodeInfo<'a> {
     pub index: usize,
     pub name: Option<&'a str>,
     pub kind_info: NodeKind,
+    pub path: NodePath,
+    pub has_children: bool,
 }
 
 pub enum NodeKind {
@@ -172,21 +294,29 @@ pub enum NodeKind {
 }
 
 impl<'a> NodeInfo<'a> {
-    fn from_scene(scene: gltf::Scene<'a>, default_scene: Option<&gltf::Scene<'a>>) -> Self {
+    fn from_scene(
+        scene: gltf::Scene<'a>,
+        default_scene: Option<&gltf::Scene<'a>>,
+        path: NodePath,
+    ) -> Self {
         Self {
             index: scene.index(),
             name: scene.name(),
             kind_info: NodeKind::Scene {
                 is_default: Some(scene.index()) == default_scene.map(|scene| scene.index()),
             },
+            path,
+            has_children: scene.nodes().next().is_some(),
         }
     }
 
-    fn from_node(node: gltf::Node<'a>) -> Self {
+    fn from_node(node: gltf::Node<'a>, path: NodePath) -> Self {
         Self {
             index: node.index(),
             name: node.name(),
             kind_info: NodeKind::Node,
+            path,
+            has_children: node.children().next().is_some(),
         }
     }
 }
@@ -194,4 +324,33 @@ impl<'a> NodeInfo<'a> {
 #[derive(Clone, Default)]
 pub struct State {
     scrollable: scrollable::State,
+    selected: Option<NodePath>,
+    /// The scenes and nodes whose children are hidden. Everything else is expanded.
+    collapsed: HashSet<NodePath>,
+    /// The expand and select buttons of each row.
+    buttons: Vec<(button::State, button::State)>,
+}
+
+impl State {
+    pub fn select(&mut self, path: NodePath) {
+        self.selected = Some(path);
+    }
+
+    /// Collapses an expanded scene or node, or expands a collapsed one.
+    pub fn toggle(&mut self, path: NodePath) {
+        if !self.collapsed.remove(&path) {
+            self.collapsed.insert(path);
+        }
+    }
+
+    /// Keeps the selection and the collapsed scenes and nodes whose paths still exist in
+    /// a newly imported version of the document.
+    pub fn reload(&mut self, document: &gltf::Document) {
+        let none_collapsed = HashSet::new();
+        let paths: HashSet<_> = tree::items(GltfTraverser::new(document, &none_collapsed))
+            .map(|node_info| node_info.path)
+            .collect();
+        self.selected = self.selected.take().filter(|path| paths.contains(path));
+        self.collapsed.retain(|path| paths.contains(path));
+    }
 }
@@ -1,4 +1,11 @@
+pub mod accessor_list;
+pub mod archive_entries;
 pub mod canvas_background;
+pub mod document_diff;
 pub mod document_statistics;
 pub mod gltf_node_tree;
+pub mod query;
+pub mod recent_files;
+pub mod size_breakdown;
+pub mod tab_bar;
 pub mod tree;
@@ -0,0 +1,65 @@
+use iced_native::widget::{
+    scrollable::{self, Scrollable},
+    text_input::{self, TextInput},
+    Column, Text,
+};
+
+use crate::analysis::query::Query;
+
+pub fn query<'a, Message, Renderer>(
+    state: &'a mut State,
+    on_change: fn(String) -> Message,
+    on_submit: Message,
+) -> Column<'a, Message, Renderer>
+where
+    Renderer: iced_native::widget::column::Renderer
+        + iced_native::widget::scrollable::Renderer
+        + iced_native::widget::text::Renderer
+        + iced_native::widget::text_input::Renderer
+        + 'a,
+    Message: Clone + 'a,
+{
+    let input = TextInput::new(
+        &mut state.input,
+        "nodes[?mesh].name",
+        &state.expression,
+        on_change,
+    )
+    .on_submit(on_submit);
+
+    let result = Scrollable::new(&mut state.scrollable).push(Text::new(state.result.as_str()));
+
+    Column::new().push(input).push(result)
+}
+
+#[derive(Clone, Default)]
+pub struct State {
+    input: text_input::State,
+    scrollable: scrollable::State,
+    expression: String,
+    result: String,
+}
+
+impl State {
+    pub fn set_expression(&mut self, expression: String) {
+        self.expression = expression;
+    }
+
+    /// Evaluates the current expression, showing the result as JSON or the parse error.
+    pub fn run(&mut self, document: &gltf::Document) {
+        self.result = match self.expression.parse::<Query>() {
+            Ok(query) => {
+                let result = query.evaluate(&Query::document_json(document));
+                serde_json::to_string_pretty(&result).unwrap_or_else(|error| error.to_string())
+            }
+            Err(error) => format!("Invalid query: {}", error),
+        };
+    }
+
+    /// Evaluates the expression again on a reloaded document, if it was run before.
+    pub fn reload(&mut self, document: &gltf::Document) {
+        if !self.result.is_empty() {
+            self.run(document);
+        }
+    }
+}
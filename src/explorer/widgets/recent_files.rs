@@ -0,0 +1,52 @@
+use iced_native::widget::{
+    button::{self, Button},
+    scrollable::{self, Scrollable},
+    Text,
+};
+
+use crate::recent_files::RecentFiles;
+
+pub fn recent_files<'a, Message, Renderer>(
+    recent_files: &'a RecentFiles,
+    state: &'a mut State,
+    on_open: fn(usize) -> Message,
+) -> Scrollable<'a, Message, Renderer>
+where
+    Renderer: iced_native::widget::scrollable::Renderer
+        + iced_native::widget::text::Renderer
+        + iced_native::widget::button::Renderer
+        + 'a,
+    Message: Clone + 'a,
+{
+    let entries = recent_files.entries();
+    state
+        .open_buttons
+        .resize_with(entries.len(), Default::default);
+
+    let State {
+        scrollable,
+        open_buttons,
+    } = state;
+
+    entries
+        .iter()
+        .zip(open_buttons.iter_mut())
+        .enumerate()
+        .fold(
+            Scrollable::new(scrollable),
+            |scrollable, (index, (entry, open_button))| {
+                let mut button = Button::new(open_button, Text::new(entry.description()));
+                // Files that have been moved or deleted are listed, but can't be opened.
+                if entry.file_size().is_some() {
+                    button = button.on_press(on_open(index));
+                }
+                scrollable.push(button)
+            },
+        )
+}
+
+#[derive(Clone, Default)]
+pub struct State {
+    scrollable: scrollable::State,
+    open_buttons: Vec<button::State>,
+}
@@ -0,0 +1,52 @@
+use iced_native::widget::{
+    scrollable::{self, Scrollable},
+    Text,
+};
+
+use crate::{analysis::size::Sizes, document::Progress};
+
+/// The sizes of a document, or how far loading the data they are worked out from got.
+pub fn sizes<'a, Message, Renderer>(
+    sizes: Option<&Sizes>,
+    progress: Option<Progress>,
+    state: &'a mut State,
+) -> Scrollable<'a, Message, Renderer>
+where
+    Renderer: iced_native::widget::scrollable::Renderer + iced_native::widget::text::Renderer + 'a,
+    Message: 'a,
+{
+    let scrollable = Scrollable::new(&mut state.scrollable).push(Text::new("Size Breakdown"));
+    let sizes = match sizes {
+        Some(sizes) => sizes,
+        None => {
+            return scrollable.push(Text::new(match progress {
+                Some(progress) => format!("Loading buffers and images: {}", progress),
+                None => String::from("Loading buffers and images"),
+            }))
+        }
+    };
+
+    let scrollable = sizes
+        .rows()
+        .into_iter()
+        .fold(scrollable, |scrollable, (label, value)| {
+            scrollable.push(Text::new(format!("{}: {}", label, value)))
+        });
+
+    sizes
+        .lists()
+        .into_iter()
+        .filter(|(_, entries)| !entries.is_empty())
+        .fold(scrollable, |scrollable, (heading, entries)| {
+            entries
+                .iter()
+                .fold(scrollable.push(Text::new(heading)), |scrollable, entry| {
+                    scrollable.push(Text::new(format!("  #{}: {}", entry.label(), entry.size())))
+                })
+        })
+}
+
+#[derive(Clone, Default)]
+pub struct State {
+    scrollable: scrollable::State,
+}
@@ -0,0 +1,43 @@
+use iced_native::widget::{
+    button::{self, Button},
+    Row, Text,
+};
+
+pub fn tab_bar<'a, Message, Renderer>(
+    names: Vec<String>,
+    active: usize,
+    state: &'a mut State,
+    on_select: fn(usize) -> Message,
+    on_close: fn(usize) -> Message,
+) -> Row<'a, Message, Renderer>
+where
+    Renderer: iced_native::widget::text::Renderer
+        + iced_native::widget::button::Renderer
+        + iced_native::widget::row::Renderer
+        + 'a,
+    Message: Clone + 'a,
+{
+    state.tab_buttons.resize_with(names.len(), Default::default);
+
+    names
+        .into_iter()
+        .zip(state.tab_buttons.iter_mut())
+        .enumerate()
+        .fold(
+            Row::new(),
+            |row, (index, (name, (select_button, close_button)))| {
+                let mut select_button = Button::new(select_button, Text::new(name));
+                if index != active {
+                    select_button = select_button.on_press(on_select(index));
+                }
+                row.push(select_button)
+                    .push(Button::new(close_button, Text::new("x")).on_press(on_close(index)))
+            },
+        )
+}
+
+#[derive(Clone, Default)]
+pub struct State {
+    /// The buttons selecting and closing each tab.
+    tab_buttons: Vec<(button::State, button::State)>,
+}
@@ -211,14 +211,23 @@ where
 
     fn on_event(
         &mut self,
-        _event: Event,
-        _layout: Layout<'_>,
-        _cursor_position: Point,
-        _messages: &mut Vec<Message>,
-        _renderer: &Renderer,
-        _clipboard: Option<&dyn Clipboard>,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<Message>,
+        renderer: &Renderer,
+        clipboard: Option<&dyn Clipboard>,
     ) {
-        // todo!()
+        for (element, layout) in self.elements.iter_mut().zip(layout.children()) {
+            element.on_event(
+                event.clone(),
+                layout,
+                cursor_position,
+                messages,
+                renderer,
+                clipboard,
+            );
+        }
     }
 
     fn draw(
@@ -344,6 +353,19 @@ pub struct State {
     //
 }
 
+/// Every item of a tree, in the order the `Tree` widget shows them.
+pub fn items<T: TreeTraverser>(mut traverser: T) -> impl Iterator<Item = T::Item> {
+    std::iter::from_fn(move || {
+        if let Some(first_child) = traverser.first_child() {
+            Some(first_child)
+        } else if let Some(next_sibling) = traverser.next_sibling() {
+            Some(next_sibling)
+        } else {
+            traverser.next_uncle().map(|(next_uncle, _)| next_uncle)
+        }
+    })
+}
+
 /// An interface for traversing trees.
 ///
 /// A traverser is an object which allows the `Tree` widget to interact with
@@ -0,0 +1,211 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use gltf::{accessor::DataType, image::Format};
+
+use crate::{
+    analysis::accessor::{self, AccessorData},
+    document::Document,
+    transform::sanitize_file_name,
+};
+
+/// Writes the selected images of a document to `dir` as PNG files, returning their paths.
+///
+/// Files are named after the image, or after its index if it is unnamed.
+pub fn write_images(
+    document: &Document,
+    indices: impl IntoIterator<Item = usize>,
+    dir: &Path,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut used_names = HashSet::new();
+    let mut written = Vec::new();
+
+    for index in indices {
+        let image = document
+            .document
+            .images()
+            .nth(index)
+            .ok_or_else(|| format!("there is no image {}", index))?;
+        let data = document.image(index)?;
+
+        let mut name = image
+            .name()
+            .map(sanitize_file_name)
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| format!("image_{}", index));
+        if !used_names.insert(name.clone()) {
+            name = format!("{}_{}", name, index);
+        }
+
+        let path = dir.join(format!("{}.png", name));
+        fs::write(&path, encode_png(data)?)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// Writes the bytes of the selected buffers to `dir`, returning the paths of the new files.
+pub fn write_buffers(
+    document: &Document,
+    indices: impl IntoIterator<Item = usize>,
+    dir: &Path,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut written = Vec::new();
+
+    for index in indices {
+        let buffer = document
+            .document
+            .buffers()
+            .nth(index)
+            .ok_or_else(|| format!("there is no buffer {}", index))?;
+        let data = document.buffer(index)?;
+
+        // The file may hold more bytes than the buffer declares.
+        let bytes = data.get(..buffer.length()).unwrap_or(data);
+        let path = dir.join(file_name("buffer", index, buffer.name(), "bin"));
+        fs::write(&path, bytes)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// Writes the bytes of the selected buffer views to `dir`, returning the paths of the new files.
+pub fn write_buffer_views(
+    document: &Document,
+    indices: impl IntoIterator<Item = usize>,
+    dir: &Path,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut written = Vec::new();
+
+    for index in indices {
+        let view = document
+            .document
+            .views()
+            .nth(index)
+            .ok_or_else(|| format!("there is no buffer view {}", index))?;
+        let bytes = crate::analysis::accessor::view_bytes(&view, document)
+            .ok_or_else(|| format!("buffer view {} is out of bounds", index))?;
+
+        let path = dir.join(file_name("buffer_view", index, view.name(), "bin"));
+        fs::write(&path, bytes)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// Decodes an accessor and writes it to `dir` as a CSV file, returning the path of the new file.
+pub fn write_accessor_csv(
+    document: &Document,
+    index: usize,
+    normalize: bool,
+    dir: &Path,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let accessor = document
+        .document
+        .accessors()
+        .nth(index)
+        .ok_or_else(|| format!("there is no accessor {}", index))?;
+    let data = read_accessor(document, &accessor, normalize)?;
+
+    let path = dir.join(file_name("accessor", index, accessor.name(), "csv"));
+    let mut file = io::BufWriter::new(fs::File::create(&path)?);
+    write_csv(&mut file, &accessor, &data)?;
+    file.flush()?;
+
+    Ok(path)
+}
+
+/// Decodes an accessor, optionally converting normalized integers to floating point values.
+pub fn read_accessor(
+    document: &Document,
+    accessor: &gltf::Accessor<'_>,
+    normalize: bool,
+) -> Result<AccessorData, Box<dyn Error>> {
+    let data = if normalize {
+        accessor::read_normalized(accessor, document)
+    } else {
+        accessor::read(accessor, document)
+    };
+    data.ok_or_else(|| format!("accessor {} is out of bounds", accessor.index()).into())
+}
+
+/// Writes accessor data as CSV, with one row per element and one column per component.
+pub fn write_csv<W: Write>(
+    out: &mut W,
+    accessor: &gltf::Accessor<'_>,
+    data: &AccessorData,
+) -> io::Result<()> {
+    let names = accessor::component_names(accessor.dimensions());
+    writeln!(out, "element,{}", names.join(","))?;
+
+    let floating_point = accessor.data_type() == DataType::F32 || accessor.normalized();
+    for (index, element) in data.elements().enumerate() {
+        write!(out, "{}", index)?;
+        for &value in element {
+            if floating_point {
+                // Every value started out as an `f32` or small integer, so this is lossless.
+                write!(out, ",{}", value as f32)?;
+            } else {
+                write!(out, ",{}", value as i64)?;
+            }
+        }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+/// Encodes decoded image data as PNG.
+pub fn encode_png(image: &gltf::image::Data) -> Result<Vec<u8>, Box<dyn Error>> {
+    let (color_type, bit_depth) = match image.format {
+        Format::R8 => (png::ColorType::Grayscale, png::BitDepth::Eight),
+        Format::R8G8 => (png::ColorType::GrayscaleAlpha, png::BitDepth::Eight),
+        Format::R8G8B8 | Format::B8G8R8 => (png::ColorType::RGB, png::BitDepth::Eight),
+        Format::R8G8B8A8 | Format::B8G8R8A8 => (png::ColorType::RGBA, png::BitDepth::Eight),
+        Format::R16 => (png::ColorType::Grayscale, png::BitDepth::Sixteen),
+        Format::R16G16 => (png::ColorType::GrayscaleAlpha, png::BitDepth::Sixteen),
+        Format::R16G16B16 => (png::ColorType::RGB, png::BitDepth::Sixteen),
+        Format::R16G16B16A16 => (png::ColorType::RGBA, png::BitDepth::Sixteen),
+    };
+
+    let mut pixels = image.pixels.clone();
+    match image.format {
+        Format::B8G8R8 => pixels
+            .chunks_exact_mut(3)
+            .for_each(|pixel| pixel.swap(0, 2)),
+        Format::B8G8R8A8 => pixels
+            .chunks_exact_mut(4)
+            .for_each(|pixel| pixel.swap(0, 2)),
+        // 16-bit samples are stored in native byte order, but PNG wants big endian.
+        _ if bit_depth == png::BitDepth::Sixteen => {
+            for sample in pixels.chunks_exact_mut(2) {
+                let value = u16::from_ne_bytes([sample[0], sample[1]]);
+                sample.copy_from_slice(&value.to_be_bytes());
+            }
+        }
+        _ => {}
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, image.width, image.height);
+    encoder.set_color(color_type);
+    encoder.set_depth(bit_depth);
+    encoder.write_header()?.write_image_data(&pixels)?;
+
+    Ok(png)
+}
+
+fn file_name(kind: &str, index: usize, name: Option<&str>, extension: &str) -> String {
+    match name.map(sanitize_file_name).filter(|name| !name.is_empty()) {
+        Some(name) => format!("{}_{}_{}.{}", kind, index, name, extension),
+        None => format!("{}_{}.{}", kind, index, extension),
+    }
+}
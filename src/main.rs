@@ -1,21 +1,77 @@
 #![forbid(unsafe_code)]
 #![forbid(rust_2018_idioms)]
 
-use std::{
-    error::Error,
-    path::{Path, PathBuf},
-};
+use std::{error::Error, path::PathBuf, process};
 
 use clap::Clap;
 use iced::{Application, Settings};
 use log::info;
 
+use crate::document::ImportError;
+
+mod analysis;
+mod archive;
+mod cli;
+mod document;
 mod explorer;
+mod export;
+mod recent_files;
+mod render;
+mod transform;
+mod watch;
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() {
     let args = Args::parse();
-    if args.dump_tree {
-        dump_tree(args.file.as_ref().unwrap());
+    if let Err(error) = run(args) {
+        eprintln!("Error: {}", error);
+        let exit_code = error
+            .downcast_ref::<ImportError>()
+            .map_or(2, ImportError::exit_code);
+        process::exit(exit_code);
+    }
+}
+
+fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    if let Some(command) = &args.command {
+        match command {
+            Command::Accessor(accessor) => accessor.run()?,
+            Command::Convert(convert) => convert.run()?,
+            Command::Extensions(extensions) => extensions.run()?,
+            Command::Extract(extract) => extract.run()?,
+            Command::Diff(diff) => {
+                if !diff.run()? {
+                    process::exit(1);
+                }
+            }
+            Command::Merge(merge) => merge.run()?,
+            Command::Optimize(optimize) => optimize.run()?,
+            Command::Query(query) => query.run()?,
+            Command::Render(render) => render.run()?,
+            Command::Report(report) => report.run()?,
+            Command::Size(size) => size.run()?,
+            Command::Stats(stats) => stats.run()?,
+            Command::Validate(validate) => {
+                if !validate.run()? {
+                    process::exit(1);
+                }
+            }
+        }
+    } else if args.dump_tree {
+        let file = args.file.as_ref().unwrap();
+        let dump_tree = || {
+            cli::dump_tree::dump_tree(
+                file,
+                args.base_dir.as_deref(),
+                args.format,
+                &args.details,
+                args.max_depth,
+            )
+        };
+        if args.watch {
+            cli::watch::watch(file, args.base_dir.as_deref(), dump_tree)?;
+        } else {
+            dump_tree()?;
+        }
     } else {
         init_logging();
         explorer::Explorer::run(Settings {
@@ -28,64 +84,43 @@ fn main() -> Result<(), Box<dyn Error>> {
 
 #[derive(Clap, Default)]
 struct Args {
+    /// The glTF file to open, or `-` to read it from standard input. A .zip archive can be
+    /// opened if it holds one glTF file, and a file in it as `archive.zip/scene.gltf`
     file: Option<PathBuf>,
+    /// The directory relative URIs are resolved against, by default the one containing
+    /// `file`, or the current directory for standard input
+    #[clap(long, requires("file"))]
+    base_dir: Option<PathBuf>,
     #[clap(long, requires("file"))]
     dump_tree: bool,
+    #[clap(long, arg_enum, default_value = "text")]
+    format: cli::Format,
+    #[clap(long, arg_enum, use_delimiter(true), requires("dump-tree"))]
+    details: Vec<cli::dump_tree::Detail>,
+    #[clap(long, requires("dump-tree"))]
+    max_depth: Option<usize>,
+    /// Print the tree again whenever the file changes on disk
+    #[clap(long, requires("dump-tree"))]
+    watch: bool,
+    #[clap(subcommand)]
+    command: Option<Command>,
 }
 
-fn dump_tree(path: &Path) {
-    use std::fmt::Write;
-
-    const UNNAMED_NODE: &str = "<unnamed node>";
-
-    let (document, _, _) = gltf::import(path).unwrap();
-
-    let mut out = String::new();
-    let mut children_stack = Vec::new();
-
-    for scene in document.scenes() {
-        out.push_str(scene.name().unwrap_or("<unnamed scene>"));
-        if document.default_scene().map(|s| s.index()) == Some(scene.index()) {
-            out.push_str(" [default]");
-        }
-        out.push('\n');
-
-        let mut root_nodes = scene.nodes().peekable();
-
-        while let Some(root_node) = root_nodes.next() {
-            if root_nodes.peek().is_some() {
-                writeln!(out, "├── {}", root_node.name().unwrap_or(UNNAMED_NODE)).ok();
-            } else {
-                writeln!(out, "└── {}", root_node.name().unwrap_or(UNNAMED_NODE)).ok();
-            }
-
-            children_stack.push(root_node.children().peekable());
-
-            while let Some(mut children) = children_stack.pop() {
-                if let Some(child) = children.next() {
-                    write!(out, "    ").ok();
-                    for node in children_stack.iter_mut() {
-                        if node.peek().is_some() {
-                            write!(out, "│   ").ok();
-                        } else {
-                            write!(out, "    ").ok();
-                        }
-                    }
-
-                    if children.peek().is_some() {
-                        writeln!(out, "├── {}", child.name().unwrap_or(UNNAMED_NODE)).ok();
-                    } else {
-                        writeln!(out, "└── {}", child.name().unwrap_or(UNNAMED_NODE)).ok();
-                    }
-
-                    children_stack.push(children);
-                    children_stack.push(child.children().peekable());
-                }
-            }
-        }
-    }
-
-    print!("{}", out);
+#[derive(Clap)]
+enum Command {
+    Accessor(cli::accessor::Accessor),
+    Convert(cli::convert::Convert),
+    Diff(cli::diff::Diff),
+    Extensions(cli::extensions::Extensions),
+    Extract(cli::extract::Extract),
+    Merge(cli::merge::Merge),
+    Optimize(cli::optimize::Optimize),
+    Query(cli::query::Query),
+    Render(cli::render::Render),
+    Report(cli::report::Report),
+    Size(cli::size::Size),
+    Stats(cli::stats::Stats),
+    Validate(cli::validate::Validate),
 }
 
 fn init_logging() {
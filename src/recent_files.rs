@@ -0,0 +1,128 @@
+//! The documents opened recently in the explorer, kept in a per-user config file.
+
+use std::{
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Local};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::{analysis::size::format_bytes, archive, document::STDIN};
+
+/// How many documents are remembered.
+const MAX_ENTRIES: usize = 10;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RecentFiles {
+    /// The most recently opened document comes first.
+    entries: Vec<Entry>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub path: PathBuf,
+    /// Seconds since the Unix epoch.
+    pub last_opened: u64,
+    /// The size of the file when the list was loaded or updated, or `None` if it no
+    /// longer existed. Looked up once, since it means opening archives.
+    #[serde(skip)]
+    size: Option<u64>,
+}
+
+impl Entry {
+    fn new(path: PathBuf, last_opened: u64) -> Self {
+        Self {
+            size: archive::file_len(&path),
+            path,
+            last_opened,
+        }
+    }
+
+    /// The size of the file, or `None` if it no longer exists.
+    pub fn file_size(&self) -> Option<u64> {
+        self.size
+    }
+
+    /// A one-line description, like `tree.glb (1.5 MiB, opened 2020-10-01 14:03)`.
+    pub fn description(&self) -> String {
+        let opened = DateTime::<Local>::from(UNIX_EPOCH + Duration::from_secs(self.last_opened))
+            .format("%Y-%m-%d %H:%M");
+        let size = match self.file_size() {
+            Some(size) => format_bytes(size as usize),
+            None => String::from("missing"),
+        };
+        format!("{} ({}, opened {})", self.path.display(), size, opened)
+    }
+}
+
+impl RecentFiles {
+    /// Reads the list from the config file. A missing or unreadable file gives an empty
+    /// list.
+    pub fn load() -> Self {
+        let path = match config_file() {
+            Some(path) => path,
+            None => return Self::default(),
+        };
+        let json = match fs::read(&path) {
+            Ok(json) => json,
+            Err(_) => return Self::default(),
+        };
+        let mut recent_files: Self = serde_json::from_slice(&json).unwrap_or_else(|error| {
+            warn!("Ignoring {}: {}", path.display(), error);
+            Self::default()
+        });
+        for entry in &mut recent_files.entries {
+            entry.size = archive::file_len(&entry.path);
+        }
+        recent_files
+    }
+
+    /// Writes the list to the config file, creating its directory if needed.
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = config_file().ok_or("there is no home or config directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Moves `path` to the top of the list, marking it as opened now.
+    pub fn add(&mut self, path: &Path) {
+        if path == Path::new(STDIN) {
+            return;
+        }
+        let path = archive::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let last_opened = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+
+        self.entries.retain(|entry| entry.path != path);
+        self.entries.insert(0, Entry::new(path, last_opened));
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
+
+/// Where the list is kept, following the conventions of each platform.
+fn config_file() -> Option<PathBuf> {
+    let dir = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| Path::new(&home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+    }?;
+    Some(dir.join("gltf_explorer").join("recent_files.json"))
+}
@@ -0,0 +1,181 @@
+//! Just enough linear algebra for the rasterizer.
+
+pub type Vec3 = [f32; 3];
+pub type Vec4 = [f32; 4];
+/// A column-major matrix, laid out like `gltf::scene::Transform::matrix`: `m[column][row]`.
+pub type Mat4 = [[f32; 4]; 4];
+
+pub const IDENTITY: Mat4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+pub fn add(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn scale(v: Vec3, s: f32) -> Vec3 {
+    [v[0] * s, v[1] * s, v[2] * s]
+}
+
+pub fn dot(a: Vec3, b: Vec3) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub fn length(v: Vec3) -> f32 {
+    dot(v, v).sqrt()
+}
+
+/// Scales `v` to unit length, leaving zero vectors as they are.
+pub fn normalize(v: Vec3) -> Vec3 {
+    let length = length(v);
+    if length > 0.0 {
+        scale(v, 1.0 / length)
+    } else {
+        v
+    }
+}
+
+pub fn mul(a: &Mat4, b: &Mat4) -> Mat4 {
+    let mut product = [[0.0; 4]; 4];
+    for (column, product_column) in product.iter_mut().enumerate() {
+        for (row, value) in product_column.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[k][row] * b[column][k]).sum();
+        }
+    }
+    product
+}
+
+pub fn transform(m: &Mat4, v: Vec4) -> Vec4 {
+    let mut result = [0.0; 4];
+    for (row, value) in result.iter_mut().enumerate() {
+        *value = (0..4).map(|column| m[column][row] * v[column]).sum();
+    }
+    result
+}
+
+pub fn transform_point(m: &Mat4, p: Vec3) -> Vec3 {
+    let [x, y, z, _] = transform(m, [p[0], p[1], p[2], 1.0]);
+    [x, y, z]
+}
+
+pub fn transform_vector(m: &Mat4, v: Vec3) -> Vec3 {
+    let [x, y, z, _] = transform(m, [v[0], v[1], v[2], 0.0]);
+    [x, y, z]
+}
+
+pub fn transpose(m: &Mat4) -> Mat4 {
+    let mut transposed = [[0.0; 4]; 4];
+    for (column, values) in m.iter().enumerate() {
+        for (row, &value) in values.iter().enumerate() {
+            transposed[row][column] = value;
+        }
+    }
+    transposed
+}
+
+/// The inverse of `m`, or `None` if it is singular.
+pub fn invert(m: &Mat4) -> Option<Mat4> {
+    // Cofactor expansion over the 2x2 minors of the upper and lower halves.
+    let s0 = m[0][0] * m[1][1] - m[1][0] * m[0][1];
+    let s1 = m[0][0] * m[1][2] - m[1][0] * m[0][2];
+    let s2 = m[0][0] * m[1][3] - m[1][0] * m[0][3];
+    let s3 = m[0][1] * m[1][2] - m[1][1] * m[0][2];
+    let s4 = m[0][1] * m[1][3] - m[1][1] * m[0][3];
+    let s5 = m[0][2] * m[1][3] - m[1][2] * m[0][3];
+
+    let c5 = m[2][2] * m[3][3] - m[3][2] * m[2][3];
+    let c4 = m[2][1] * m[3][3] - m[3][1] * m[2][3];
+    let c3 = m[2][1] * m[3][2] - m[3][1] * m[2][2];
+    let c2 = m[2][0] * m[3][3] - m[3][0] * m[2][3];
+    let c1 = m[2][0] * m[3][2] - m[3][0] * m[2][2];
+    let c0 = m[2][0] * m[3][1] - m[3][0] * m[2][1];
+
+    let determinant = s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0;
+    if determinant.abs() <= f32::EPSILON * f32::EPSILON {
+        return None;
+    }
+    let d = 1.0 / determinant;
+
+    Some([
+        [
+            (m[1][1] * c5 - m[1][2] * c4 + m[1][3] * c3) * d,
+            (-m[0][1] * c5 + m[0][2] * c4 - m[0][3] * c3) * d,
+            (m[3][1] * s5 - m[3][2] * s4 + m[3][3] * s3) * d,
+            (-m[2][1] * s5 + m[2][2] * s4 - m[2][3] * s3) * d,
+        ],
+        [
+            (-m[1][0] * c5 + m[1][2] * c2 - m[1][3] * c1) * d,
+            (m[0][0] * c5 - m[0][2] * c2 + m[0][3] * c1) * d,
+            (-m[3][0] * s5 + m[3][2] * s2 - m[3][3] * s1) * d,
+            (m[2][0] * s5 - m[2][2] * s2 + m[2][3] * s1) * d,
+        ],
+        [
+            (m[1][0] * c4 - m[1][1] * c2 + m[1][3] * c0) * d,
+            (-m[0][0] * c4 + m[0][1] * c2 - m[0][3] * c0) * d,
+            (m[3][0] * s4 - m[3][1] * s2 + m[3][3] * s0) * d,
+            (-m[2][0] * s4 + m[2][1] * s2 - m[2][3] * s0) * d,
+        ],
+        [
+            (-m[1][0] * c3 + m[1][1] * c1 - m[1][2] * c0) * d,
+            (m[0][0] * c3 - m[0][1] * c1 + m[0][2] * c0) * d,
+            (-m[3][0] * s3 + m[3][1] * s1 - m[3][2] * s0) * d,
+            (m[2][0] * s3 - m[2][1] * s1 + m[2][2] * s0) * d,
+        ],
+    ])
+}
+
+/// A camera-to-world matrix for a camera at `eye` looking at `target`.
+pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Mat4 {
+    let z = normalize(sub(eye, target));
+    let x = normalize(cross(up, z));
+    let y = cross(z, x);
+    [
+        [x[0], x[1], x[2], 0.0],
+        [y[0], y[1], y[2], 0.0],
+        [z[0], z[1], z[2], 0.0],
+        [eye[0], eye[1], eye[2], 1.0],
+    ]
+}
+
+/// The perspective projection from the glTF specification, infinite if `zfar` is `None`.
+pub fn perspective(yfov: f32, aspect_ratio: f32, znear: f32, zfar: Option<f32>) -> Mat4 {
+    let t = (yfov / 2.0).tan();
+    let (z, w) = match zfar {
+        Some(zfar) => (
+            (zfar + znear) / (znear - zfar),
+            2.0 * zfar * znear / (znear - zfar),
+        ),
+        None => (-1.0, -2.0 * znear),
+    };
+    [
+        [1.0 / (aspect_ratio * t), 0.0, 0.0, 0.0],
+        [0.0, 1.0 / t, 0.0, 0.0],
+        [0.0, 0.0, z, -1.0],
+        [0.0, 0.0, w, 0.0],
+    ]
+}
+
+/// The orthographic projection from the glTF specification.
+pub fn orthographic(xmag: f32, ymag: f32, znear: f32, zfar: f32) -> Mat4 {
+    [
+        [1.0 / xmag, 0.0, 0.0, 0.0],
+        [0.0, 1.0 / ymag, 0.0, 0.0],
+        [0.0, 0.0, 2.0 / (znear - zfar), 0.0],
+        [0.0, 0.0, (zfar + znear) / (znear - zfar), 1.0],
+    ]
+}
@@ -0,0 +1,577 @@
+//! A small CPU rasterizer for preview images.
+//!
+//! Meshes are drawn with their base color factor, base color texture, vertex colors and
+//! emissive factor, lit by the scene's KHR_lights_punctual lights with Lambertian
+//! diffuse shading. Skins, morph targets and the other material properties are ignored.
+
+use std::{error::Error, f32::consts::FRAC_PI_4};
+
+use gltf::{
+    camera::Projection,
+    image::Format,
+    khr_lights_punctual::Kind,
+    material::AlphaMode,
+    mesh::{Mode, Semantic},
+    texture::WrappingMode,
+};
+
+use crate::{analysis::accessor, document::Document};
+use math::{Mat4, Vec3, Vec4};
+use raster::{Framebuffer, Varyings, Vertex};
+
+mod math;
+mod raster;
+
+/// Supersampling factor along each axis, for antialiasing.
+const SAMPLES: u32 = 2;
+
+/// Light reaching surfaces from every direction, relative to the base color.
+const AMBIENT: f32 = 0.15;
+
+pub struct Options {
+    pub width: u32,
+    pub height: u32,
+    /// The scene to render instead of the default scene.
+    pub scene: Option<usize>,
+    /// The camera to look through instead of framing the whole scene.
+    pub camera: Option<usize>,
+}
+
+/// Renders a scene into an RGBA image with a transparent background.
+pub fn render(document: &Document, options: &Options) -> Result<gltf::image::Data, Box<dyn Error>> {
+    let gltf = &document.document;
+    let scene = match options.scene {
+        Some(index) => gltf
+            .scenes()
+            .nth(index)
+            .ok_or_else(|| format!("there is no scene {}", index))?,
+        None => gltf
+            .default_scene()
+            .or_else(|| gltf.scenes().next())
+            .ok_or("the document has no scenes")?,
+    };
+
+    let mut contents = SceneContents::default();
+    for node in scene.nodes() {
+        contents.visit(node, &math::IDENTITY);
+    }
+
+    let meshes: Vec<MeshData<'_>> = contents
+        .primitives
+        .iter()
+        .filter_map(|(primitive, world)| MeshData::new(document, primitive, world))
+        .collect();
+
+    let aspect_ratio = options.width as f32 / options.height as f32;
+    let (camera_world, projection) = match options.camera {
+        Some(index) => {
+            let (camera, world) = contents
+                .cameras
+                .iter()
+                .find(|(camera, _)| camera.index() == index)
+                .ok_or_else(|| {
+                    format!(
+                        "camera {} is not used by any node in scene {}",
+                        index,
+                        scene.index()
+                    )
+                })?;
+            (*world, camera_projection(camera, aspect_ratio))
+        }
+        None => framing_camera(&meshes, aspect_ratio),
+    };
+    let view = math::invert(&camera_world).ok_or("the camera transform is not invertible")?;
+    let view_projection = math::mul(&projection, &view);
+    let eye = math::transform_point(&camera_world, [0.0; 3]);
+
+    let lights: Vec<Light> = if contents.lights.is_empty() {
+        // A light over the viewer's shoulder, so that unlit scenes still show their shape.
+        let direction = math::transform_vector(&camera_world, [-0.3, -0.5, -1.0]);
+        vec![Light::directional(math::normalize(direction), [1.0; 3])]
+    } else {
+        contents.lights.iter().map(Light::from_node).collect()
+    };
+
+    let mut framebuffer = Framebuffer::new(options.width * SAMPLES, options.height * SAMPLES);
+    for mesh in &meshes {
+        let vertices: Vec<Vertex> = (0..mesh.positions.len())
+            .map(|i| mesh.vertex(i, &view_projection))
+            .collect();
+        let mut shade = |varyings: &Varyings| mesh.material.shade(varyings, eye, &lights);
+        for triangle in &mesh.triangles {
+            let [a, b, c] = *triangle;
+            if let (Some(a), Some(b), Some(c)) = (vertices.get(a), vertices.get(b), vertices.get(c))
+            {
+                framebuffer.draw_triangle([*a, *b, *c], &mut shade);
+            }
+        }
+    }
+
+    Ok(gltf::image::Data {
+        pixels: framebuffer.resolve(SAMPLES),
+        format: Format::R8G8B8A8,
+        width: options.width,
+        height: options.height,
+    })
+}
+
+/// Everything in a scene that affects the image, with world transforms.
+#[derive(Default)]
+struct SceneContents<'a> {
+    primitives: Vec<(gltf::Primitive<'a>, Mat4)>,
+    cameras: Vec<(gltf::Camera<'a>, Mat4)>,
+    lights: Vec<(gltf::khr_lights_punctual::Light<'a>, Mat4)>,
+}
+
+impl<'a> SceneContents<'a> {
+    fn visit(&mut self, node: gltf::Node<'a>, parent: &Mat4) {
+        let world = math::mul(parent, &node.transform().matrix());
+        if let Some(mesh) = node.mesh() {
+            self.primitives
+                .extend(mesh.primitives().map(|primitive| (primitive, world)));
+        }
+        if let Some(camera) = node.camera() {
+            self.cameras.push((camera, world));
+        }
+        if let Some(light) = node.light() {
+            self.lights.push((light, world));
+        }
+        for child in node.children() {
+            self.visit(child, &world);
+        }
+    }
+}
+
+fn camera_projection(camera: &gltf::Camera<'_>, aspect_ratio: f32) -> Mat4 {
+    match camera.projection() {
+        // The image size takes precedence over the camera's aspect ratio.
+        Projection::Perspective(perspective) => math::perspective(
+            perspective.yfov(),
+            aspect_ratio,
+            perspective.znear(),
+            perspective.zfar(),
+        ),
+        Projection::Orthographic(orthographic) => math::orthographic(
+            orthographic.ymag() * aspect_ratio,
+            orthographic.ymag(),
+            orthographic.znear(),
+            orthographic.zfar(),
+        ),
+    }
+}
+
+/// A camera looking at every mesh from the front, above and to the right.
+fn framing_camera(meshes: &[MeshData<'_>], aspect_ratio: f32) -> (Mat4, Mat4) {
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for position in meshes.iter().flat_map(|mesh| &mesh.positions) {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(position[axis]);
+            max[axis] = max[axis].max(position[axis]);
+        }
+    }
+    let (center, radius) = if min[0] <= max[0] {
+        let center = math::scale(math::add(min, max), 0.5);
+        (center, (math::length(math::sub(max, min)) / 2.0).max(1e-3))
+    } else {
+        ([0.0; 3], 1.0)
+    };
+
+    let yfov = FRAC_PI_4;
+    let fov = if aspect_ratio < 1.0 {
+        2.0 * ((yfov / 2.0).tan() * aspect_ratio).atan()
+    } else {
+        yfov
+    };
+    let distance = radius / (fov / 2.0).sin();
+    let direction = math::normalize([0.6, 0.45, 1.0]);
+    let eye = math::add(center, math::scale(direction, distance));
+
+    let camera_world = math::look_at(eye, center, [0.0, 1.0, 0.0]);
+    let znear = (distance - radius).max(distance * 1e-3);
+    let zfar = distance + radius;
+    (
+        camera_world,
+        math::perspective(yfov, aspect_ratio, znear, Some(zfar)),
+    )
+}
+
+/// A primitive's vertices in world space, split into triangles.
+struct MeshData<'a> {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    tex_coords: Vec<[f32; 2]>,
+    colors: Vec<Vec4>,
+    triangles: Vec<[usize; 3]>,
+    material: Material<'a>,
+}
+
+impl<'a> MeshData<'a> {
+    fn new(document: &'a Document, primitive: &gltf::Primitive<'a>, world: &Mat4) -> Option<Self> {
+        // gltf doesn't check accessor types against the semantics, so an attribute with
+        // fewer components than needed is skipped rather than indexed.
+        let read = |semantic: &Semantic, components: usize| {
+            primitive
+                .get(semantic)
+                .and_then(|accessor| accessor::read_normalized(&accessor, document))
+                .filter(|data| data.components >= components)
+        };
+
+        let positions: Vec<Vec3> = read(&Semantic::Positions, 3)?
+            .elements()
+            .map(|p| math::transform_point(world, [p[0] as f32, p[1] as f32, p[2] as f32]))
+            .collect();
+
+        let indices: Vec<usize> = match primitive.indices() {
+            Some(indices) => accessor::read(&indices, document)?
+                .values
+                .iter()
+                .map(|&index| index as usize)
+                .collect(),
+            None => (0..positions.len()).collect(),
+        };
+        let mut triangles = triangles(primitive.mode(), &indices);
+        triangles.retain(|triangle| triangle.iter().all(|&index| index < positions.len()));
+
+        let normal_matrix = math::transpose(&math::invert(world).unwrap_or(math::IDENTITY));
+        let mut normals: Vec<Vec3> = match read(&Semantic::Normals, 3) {
+            Some(normals) => normals
+                .elements()
+                .map(|n| {
+                    let normal = [n[0] as f32, n[1] as f32, n[2] as f32];
+                    math::normalize(math::transform_vector(&normal_matrix, normal))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        if normals.len() != positions.len() {
+            normals = flat_normals(&positions, &triangles);
+        }
+
+        let material = Material::new(document, &primitive.material());
+        let tex_coords = read(&Semantic::TexCoords(material.tex_coord), 2)
+            .map(|data| {
+                data.elements()
+                    .map(|uv| [uv[0] as f32, uv[1] as f32])
+                    .collect()
+            })
+            .unwrap_or_default();
+        let colors = read(&Semantic::Colors(0), 3)
+            .map(|data| {
+                data.elements()
+                    .map(|c| [c[0], c[1], c[2], c.get(3).copied().unwrap_or(1.0)])
+                    .map(|c| [c[0] as f32, c[1] as f32, c[2] as f32, c[3] as f32])
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            positions,
+            normals,
+            tex_coords,
+            colors,
+            triangles,
+            material,
+        })
+    }
+
+    fn vertex(&self, index: usize, view_projection: &Mat4) -> Vertex {
+        let [x, y, z] = self.positions[index];
+        let normal = self.normals.get(index).copied().unwrap_or([0.0; 3]);
+        let uv = self.tex_coords.get(index).copied().unwrap_or([0.0; 2]);
+        let color = self.colors.get(index).copied().unwrap_or([1.0; 4]);
+        Vertex {
+            position: math::transform(view_projection, [x, y, z, 1.0]),
+            varyings: [
+                x, y, z, normal[0], normal[1], normal[2], uv[0], uv[1], color[0], color[1],
+                color[2], color[3],
+            ],
+        }
+    }
+}
+
+fn triangles(mode: Mode, indices: &[usize]) -> Vec<[usize; 3]> {
+    match mode {
+        Mode::Triangles => indices
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+            .collect(),
+        Mode::TriangleStrip => indices
+            .windows(3)
+            .enumerate()
+            .map(|(i, window)| {
+                // Every other triangle is flipped to keep the winding order consistent.
+                if i % 2 == 0 {
+                    [window[0], window[1], window[2]]
+                } else {
+                    [window[1], window[0], window[2]]
+                }
+            })
+            .collect(),
+        Mode::TriangleFan => indices
+            .windows(2)
+            .skip(1)
+            .map(|window| [indices[0], window[0], window[1]])
+            .collect(),
+        Mode::Points | Mode::Lines | Mode::LineLoop | Mode::LineStrip => Vec::new(),
+    }
+}
+
+/// Vertex normals for primitives without them, averaged over the adjacent faces.
+fn flat_normals(positions: &[Vec3], triangles: &[[usize; 3]]) -> Vec<Vec3> {
+    let mut normals = vec![[0.0; 3]; positions.len()];
+    for &[a, b, c] in triangles {
+        if let (Some(&pa), Some(&pb), Some(&pc)) =
+            (positions.get(a), positions.get(b), positions.get(c))
+        {
+            let normal = math::cross(math::sub(pb, pa), math::sub(pc, pa));
+            for &vertex in &[a, b, c] {
+                normals[vertex] = math::add(normals[vertex], normal);
+            }
+        }
+    }
+    normals.into_iter().map(math::normalize).collect()
+}
+
+struct Material<'a> {
+    base_color: Vec4,
+    texture: Option<Texture<'a>>,
+    tex_coord: u32,
+    emissive: Vec3,
+    alpha_mode: AlphaMode,
+    alpha_cutoff: f32,
+}
+
+impl<'a> Material<'a> {
+    fn new(document: &'a Document, material: &gltf::Material<'a>) -> Self {
+        let pbr = material.pbr_metallic_roughness();
+        let info = pbr.base_color_texture();
+        Self {
+            base_color: pbr.base_color_factor(),
+            texture: info.as_ref().and_then(|info| {
+                let texture = info.texture();
+                let sampler = texture.sampler();
+                Some(Texture {
+                    image: document.image(texture.source().index()).ok()?,
+                    wrap_s: sampler.wrap_s(),
+                    wrap_t: sampler.wrap_t(),
+                })
+            }),
+            tex_coord: info.map_or(0, |info| info.tex_coord()),
+            emissive: material.emissive_factor(),
+            alpha_mode: material.alpha_mode(),
+            alpha_cutoff: material.alpha_cutoff(),
+        }
+    }
+
+    fn shade(&self, varyings: &Varyings, eye: Vec3, lights: &[Light]) -> Option<Vec4> {
+        let position = [varyings[0], varyings[1], varyings[2]];
+        let mut normal = math::normalize([varyings[3], varyings[4], varyings[5]]);
+        let uv = [varyings[6], varyings[7]];
+
+        let mut base = self.base_color;
+        if let Some(texture) = &self.texture {
+            let texel = texture.sample(uv);
+            for (value, texel) in base.iter_mut().zip(&texel) {
+                *value *= texel;
+            }
+        }
+        for (value, color) in base.iter_mut().zip(&varyings[8..12]) {
+            *value *= color;
+        }
+
+        match self.alpha_mode {
+            AlphaMode::Opaque => base[3] = 1.0,
+            AlphaMode::Mask if base[3] < self.alpha_cutoff => return None,
+            AlphaMode::Mask => base[3] = 1.0,
+            // Blended surfaces are drawn in no particular order, so they only fade into the
+            // background.
+            AlphaMode::Blend => {}
+        }
+
+        // Back faces are lit like front faces; a preview showing something is more useful
+        // than one with holes where single-sided geometry is seen from behind.
+        if math::dot(normal, math::sub(eye, position)) < 0.0 {
+            normal = math::scale(normal, -1.0);
+        }
+
+        let mut irradiance = [AMBIENT; 3];
+        for light in lights {
+            let received = light.irradiance(position, normal);
+            irradiance = math::add(irradiance, received);
+        }
+
+        Some([
+            base[0] * irradiance[0] + self.emissive[0],
+            base[1] * irradiance[1] + self.emissive[1],
+            base[2] * irradiance[2] + self.emissive[2],
+            base[3],
+        ])
+    }
+}
+
+struct Texture<'a> {
+    image: &'a gltf::image::Data,
+    wrap_s: WrappingMode,
+    wrap_t: WrappingMode,
+}
+
+impl<'a> Texture<'a> {
+    /// Bilinearly filtered linear color at `uv`.
+    fn sample(&self, uv: [f32; 2]) -> Vec4 {
+        let (width, height) = (self.image.width as f32, self.image.height as f32);
+        let x = uv[0] * width - 0.5;
+        let y = uv[1] * height - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+
+        let texel = |dx: f32, dy: f32| {
+            let tx = wrap(x0 as i64 + dx as i64, self.image.width, self.wrap_s);
+            let ty = wrap(y0 as i64 + dy as i64, self.image.height, self.wrap_t);
+            self.texel(tx, ty)
+        };
+        let (t00, t10, t01, t11) = (
+            texel(0.0, 0.0),
+            texel(1.0, 0.0),
+            texel(0.0, 1.0),
+            texel(1.0, 1.0),
+        );
+
+        let mut color = [0.0; 4];
+        for (channel, value) in color.iter_mut().enumerate() {
+            let top = t00[channel] + (t10[channel] - t00[channel]) * fx;
+            let bottom = t01[channel] + (t11[channel] - t01[channel]) * fx;
+            *value = top + (bottom - top) * fy;
+        }
+        color
+    }
+
+    fn texel(&self, x: u32, y: u32) -> Vec4 {
+        let image = self.image;
+        let (channels, bytes_per_channel) = match image.format {
+            Format::R8 => (1, 1),
+            Format::R8G8 => (2, 1),
+            Format::R8G8B8 | Format::B8G8R8 => (3, 1),
+            Format::R8G8B8A8 | Format::B8G8R8A8 => (4, 1),
+            Format::R16 => (1, 2),
+            Format::R16G16 => (2, 2),
+            Format::R16G16B16 => (3, 2),
+            Format::R16G16B16A16 => (4, 2),
+        };
+        let start = (y as usize * image.width as usize + x as usize) * channels * bytes_per_channel;
+        let value = |channel: usize| {
+            let offset = start + channel * bytes_per_channel;
+            match bytes_per_channel {
+                1 => image
+                    .pixels
+                    .get(offset)
+                    .map_or(0.0, |&value| value as f32 / 255.0),
+                _ => image.pixels.get(offset..offset + 2).map_or(0.0, |bytes| {
+                    u16::from_ne_bytes([bytes[0], bytes[1]]) as f32 / 65535.0
+                }),
+            }
+        };
+
+        let [r, g, b, a] = match channels {
+            1 => [value(0), value(0), value(0), 1.0],
+            2 => [value(0), value(0), value(0), value(1)],
+            3 => [value(0), value(1), value(2), 1.0],
+            _ => [value(0), value(1), value(2), value(3)],
+        };
+        let [r, b] = match image.format {
+            Format::B8G8R8 | Format::B8G8R8A8 => [b, r],
+            _ => [r, b],
+        };
+
+        // Base color textures are sRGB encoded.
+        [
+            raster::srgb_to_linear(r),
+            raster::srgb_to_linear(g),
+            raster::srgb_to_linear(b),
+            a,
+        ]
+    }
+}
+
+fn wrap(coordinate: i64, size: u32, mode: WrappingMode) -> u32 {
+    let size = i64::from(size.max(1));
+    let wrapped = match mode {
+        WrappingMode::ClampToEdge => coordinate.clamp(0, size - 1),
+        WrappingMode::Repeat => coordinate.rem_euclid(size),
+        WrappingMode::MirroredRepeat => {
+            let period = coordinate.rem_euclid(2 * size);
+            if period < size {
+                period
+            } else {
+                2 * size - 1 - period
+            }
+        }
+    };
+    wrapped as u32
+}
+
+struct Light {
+    kind: Kind,
+    /// Color multiplied by intensity.
+    color: Vec3,
+    position: Vec3,
+    /// The direction the light shines in.
+    direction: Vec3,
+    range: Option<f32>,
+}
+
+impl Light {
+    fn directional(direction: Vec3, color: Vec3) -> Self {
+        Self {
+            kind: Kind::Directional,
+            color,
+            position: [0.0; 3],
+            direction,
+            range: None,
+        }
+    }
+
+    fn from_node((light, world): &(gltf::khr_lights_punctual::Light<'_>, Mat4)) -> Self {
+        Self {
+            kind: light.kind(),
+            color: math::scale(light.color(), light.intensity()),
+            position: math::transform_point(world, [0.0; 3]),
+            direction: math::normalize(math::transform_vector(world, [0.0, 0.0, -1.0])),
+            range: light.range(),
+        }
+    }
+
+    /// Light arriving at a surface, following the KHR_lights_punctual attenuation rules.
+    fn irradiance(&self, position: Vec3, normal: Vec3) -> Vec3 {
+        let (to_light, attenuation) = match self.kind {
+            Kind::Directional => (math::scale(self.direction, -1.0), 1.0),
+            Kind::Point | Kind::Spot { .. } => {
+                let offset = math::sub(self.position, position);
+                let distance = math::length(offset).max(1e-4);
+                let window = self.range.map_or(1.0, |range| {
+                    (1.0 - (distance / range).powi(4)).clamp(0.0, 1.0).powi(2)
+                });
+                (
+                    math::scale(offset, 1.0 / distance),
+                    window / (distance * distance),
+                )
+            }
+        };
+
+        let cone = match self.kind {
+            Kind::Spot {
+                inner_cone_angle,
+                outer_cone_angle,
+            } => {
+                let cos_outer = outer_cone_angle.cos();
+                let scale = 1.0 / (inner_cone_angle.cos() - cos_outer).max(1e-3);
+                let cd = math::dot(self.direction, math::scale(to_light, -1.0));
+                ((cd - cos_outer) * scale).clamp(0.0, 1.0).powi(2)
+            }
+            Kind::Directional | Kind::Point => 1.0,
+        };
+
+        let lambert = math::dot(normal, to_light).max(0.0);
+        math::scale(self.color, lambert * attenuation * cone)
+    }
+}
@@ -0,0 +1,214 @@
+//! Triangle rasterization with depth testing and perspective-correct interpolation.
+
+use super::math::Vec4;
+
+/// Number of values interpolated across a triangle: world position, normal, texture
+/// coordinates and vertex color.
+pub const VARYINGS: usize = 12;
+
+pub type Varyings = [f32; VARYINGS];
+
+#[derive(Clone, Copy, Debug)]
+pub struct Vertex {
+    /// Position in clip space.
+    pub position: Vec4,
+    pub varyings: Varyings,
+}
+
+/// Linear color and depth for every pixel.
+pub struct Framebuffer {
+    pub width: u32,
+    pub height: u32,
+    color: Vec<Vec4>,
+    depth: Vec<f32>,
+}
+
+impl Framebuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let pixels = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            color: vec![[0.0; 4]; pixels],
+            depth: vec![f32::INFINITY; pixels],
+        }
+    }
+
+    /// Draws a triangle, calling `shade` with the interpolated varyings of every covered
+    /// pixel. Pixels `shade` returns `None` for are discarded.
+    pub fn draw_triangle<F>(&mut self, triangle: [Vertex; 3], shade: &mut F)
+    where
+        F: FnMut(&Varyings) -> Option<Vec4>,
+    {
+        let clipped = clip_near(&triangle);
+        for i in 1..clipped.len().saturating_sub(1) {
+            self.draw_clipped([clipped[0], clipped[i], clipped[i + 1]], shade);
+        }
+    }
+
+    fn draw_clipped<F>(&mut self, triangle: [Vertex; 3], shade: &mut F)
+    where
+        F: FnMut(&Varyings) -> Option<Vec4>,
+    {
+        let (width, height) = (self.width as f32, self.height as f32);
+        let screen: Vec<[f32; 4]> = triangle
+            .iter()
+            .map(|vertex| {
+                let [x, y, z, w] = vertex.position;
+                let inverse_w = 1.0 / w;
+                [
+                    (x * inverse_w * 0.5 + 0.5) * width,
+                    (0.5 - y * inverse_w * 0.5) * height,
+                    z * inverse_w,
+                    inverse_w,
+                ]
+            })
+            .collect();
+
+        // Both windings are drawn, so the sign of the area doesn't matter.
+        let area = edge(screen[0], screen[1], screen[2]);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+
+        let min_x = screen.iter().map(|p| p[0]).fold(f32::INFINITY, f32::min);
+        let max_x = screen
+            .iter()
+            .map(|p| p[0])
+            .fold(f32::NEG_INFINITY, f32::max);
+        let min_y = screen.iter().map(|p| p[1]).fold(f32::INFINITY, f32::min);
+        let max_y = screen
+            .iter()
+            .map(|p| p[1])
+            .fold(f32::NEG_INFINITY, f32::max);
+        let x_range = min_x.max(0.0).floor() as u32..(max_x.min(width).ceil() as u32);
+        let y_range = min_y.max(0.0).floor() as u32..(max_y.min(height).ceil() as u32);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                let p = [x as f32 + 0.5, y as f32 + 0.5, 0.0, 0.0];
+                let b0 = edge(screen[1], screen[2], p) / area;
+                let b1 = edge(screen[2], screen[0], p) / area;
+                let b2 = edge(screen[0], screen[1], p) / area;
+                if b0 < 0.0 || b1 < 0.0 || b2 < 0.0 {
+                    continue;
+                }
+
+                // Depth is affine in screen space, everything else only after dividing by w.
+                let depth = b0 * screen[0][2] + b1 * screen[1][2] + b2 * screen[2][2];
+                let pixel = (y * self.width + x) as usize;
+                if depth > 1.0 || depth >= self.depth[pixel] {
+                    continue;
+                }
+
+                let weights = [b0 * screen[0][3], b1 * screen[1][3], b2 * screen[2][3]];
+                let total: f32 = weights.iter().sum();
+                let mut varyings = [0.0; VARYINGS];
+                for (i, varying) in varyings.iter_mut().enumerate() {
+                    *varying = (0..3)
+                        .map(|v| weights[v] * triangle[v].varyings[i])
+                        .sum::<f32>()
+                        / total;
+                }
+
+                if let Some(color) = shade(&varyings) {
+                    self.color[pixel] = color;
+                    self.depth[pixel] = depth;
+                }
+            }
+        }
+    }
+
+    /// Averages blocks of `factor` by `factor` pixels and encodes the result as 8-bit sRGB
+    /// with straight alpha.
+    pub fn resolve(&self, factor: u32) -> Vec<u8> {
+        let width = self.width / factor;
+        let height = self.height / factor;
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+
+        for y in 0..height {
+            for x in 0..width {
+                // Weighting by alpha keeps the background from darkening the edges.
+                let mut sum = [0.0; 4];
+                for sy in 0..factor {
+                    for sx in 0..factor {
+                        let index = ((y * factor + sy) * self.width + x * factor + sx) as usize;
+                        let [r, g, b, a] = self.color[index];
+                        sum[0] += r * a;
+                        sum[1] += g * a;
+                        sum[2] += b * a;
+                        sum[3] += a;
+                    }
+                }
+
+                let alpha = sum[3] / (factor * factor) as f32;
+                for &channel in &sum[..3] {
+                    let value = if sum[3] > 0.0 { channel / sum[3] } else { 0.0 };
+                    pixels.push(to_byte(linear_to_srgb(value)));
+                }
+                pixels.push(to_byte(alpha));
+            }
+        }
+
+        pixels
+    }
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `c`.
+fn edge(a: [f32; 4], b: [f32; 4], c: [f32; 4]) -> f32 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+/// Clips a triangle against the near plane, returning a convex polygon of up to four
+/// vertices. Everything else is clipped per pixel.
+fn clip_near(triangle: &[Vertex; 3]) -> Vec<Vertex> {
+    // Also keeps w away from zero for orthographic and infinite projections.
+    let distance = |vertex: &Vertex| vertex.position[2] + vertex.position[3] - 1e-6;
+
+    let mut polygon = Vec::with_capacity(4);
+    for i in 0..3 {
+        let current = &triangle[i];
+        let next = &triangle[(i + 1) % 3];
+        let (d_current, d_next) = (distance(current), distance(next));
+
+        if d_current >= 0.0 {
+            polygon.push(*current);
+        }
+        if (d_current >= 0.0) != (d_next >= 0.0) {
+            let t = d_current / (d_current - d_next);
+            polygon.push(lerp(current, next, t));
+        }
+    }
+    polygon
+}
+
+fn lerp(a: &Vertex, b: &Vertex, t: f32) -> Vertex {
+    let mut vertex = *a;
+    for (value, &other) in vertex.position.iter_mut().zip(&b.position) {
+        *value += (other - *value) * t;
+    }
+    for (value, &other) in vertex.varyings.iter_mut().zip(&b.varyings) {
+        *value += (other - *value) * t;
+    }
+    vertex
+}
+
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn to_byte(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
@@ -0,0 +1,224 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs,
+    path::Path,
+};
+
+use serde_json::{json, Value};
+
+use super::references::as_index;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// A single .glb file with all buffers and images in its binary chunk.
+    Glb,
+    /// A .gltf file whose buffer and images are either written next to it or embedded as
+    /// data URIs.
+    Gltf { embed: bool },
+}
+
+/// Re-packs the buffers and images of a document and writes it to `output`.
+///
+/// `buffers` holds the data of the buffers `json` refers to, and relative image URIs are
+/// resolved against `base`.
+/// All buffer views are gathered into a single buffer. Buffer views that only held image
+/// data are dropped, and the images are either moved to the end of the new buffer (for
+/// `Target::Glb`) or turned into URIs (for `Target::Gltf`).
+pub fn convert(
+    buffers: &[&[u8]],
+    base: &Path,
+    mut json: Value,
+    output: &Path,
+    target: Target,
+) -> Result<(), Box<dyn Error>> {
+    let views = take_array(&mut json, "bufferViews");
+    let mut images = take_array(&mut json, "images");
+
+    let mut image_data = Vec::with_capacity(images.len());
+    let mut image_views = HashSet::new();
+    for (index, image) in images.iter_mut().enumerate() {
+        let object = image
+            .as_object_mut()
+            .ok_or_else(|| format!("image {} is not an object", index))?;
+
+        let data = if let Some(view) = object.remove("bufferView") {
+            let view = as_index(&view)
+                .ok_or_else(|| format!("image {} has an invalid bufferView", index))?;
+            image_views.insert(view);
+            views
+                .get(view)
+                .and_then(|view| view_bytes(buffers, view))
+                .ok_or_else(|| format!("buffer view {} of image {} is out of bounds", view, index))?
+                .to_vec()
+        } else if let Some(uri) = object.remove("uri") {
+            let uri = uri
+                .as_str()
+                .ok_or_else(|| format!("image {} has an invalid uri", index))?;
+            if let Some(mime_type) = super::data_uri_mime_type(uri) {
+                object.insert(String::from("mimeType"), Value::from(mime_type));
+            }
+            super::read_uri(base, uri)?
+        } else {
+            return Err(format!("image {} has neither a uri nor a bufferView", index).into());
+        };
+
+        let mime_type = object
+            .get("mimeType")
+            .and_then(Value::as_str)
+            .or_else(|| super::image_mime_type(&data))
+            .unwrap_or("application/octet-stream")
+            .to_owned();
+        image_data.push((data, mime_type));
+    }
+
+    // Image views that are also used by something else, like an extension, have to stay.
+    let mut used_views = HashSet::new();
+    visit_buffer_views(&mut json, &mut |view| {
+        if let Some(view) = as_index(view) {
+            used_views.insert(view);
+        }
+    });
+
+    let mut bin = Vec::new();
+    let mut new_views = Vec::with_capacity(views.len());
+    let mut view_indices = HashMap::new();
+    for (index, mut view) in views.into_iter().enumerate() {
+        if image_views.contains(&index) && !used_views.contains(&index) {
+            continue;
+        }
+
+        let bytes = view_bytes(buffers, &view)
+            .ok_or_else(|| format!("buffer view {} is out of bounds", index))?;
+        align_to_four(&mut bin);
+        view["buffer"] = json!(0);
+        view["byteOffset"] = json!(bin.len());
+        bin.extend_from_slice(bytes);
+
+        view_indices.insert(index, new_views.len());
+        new_views.push(view);
+    }
+
+    visit_buffer_views(&mut json, &mut |view| {
+        if let Some(new_index) = as_index(view).and_then(|index| view_indices.get(&index)) {
+            *view = json!(new_index);
+        }
+    });
+
+    let mut image_file_names = HashSet::new();
+    for (index, (image, (data, mime_type))) in images.iter_mut().zip(image_data).enumerate() {
+        match target {
+            Target::Glb => {
+                align_to_four(&mut bin);
+                new_views.push(json!({
+                    "buffer": 0,
+                    "byteOffset": bin.len(),
+                    "byteLength": data.len(),
+                }));
+                bin.extend_from_slice(&data);
+                image["bufferView"] = json!(new_views.len() - 1);
+                image["mimeType"] = json!(mime_type);
+            }
+            Target::Gltf { embed: true } => {
+                image["uri"] = json!(super::data_uri(&mime_type, &data));
+            }
+            Target::Gltf { embed: false } => {
+                let name = image
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .map(super::sanitize_file_name)
+                    .filter(|name| !name.is_empty() && !image_file_names.contains(name))
+                    .unwrap_or_else(|| index.to_string());
+                let path = super::sibling_path(
+                    output,
+                    &format!("_{}", name),
+                    super::image_extension(&mime_type),
+                );
+                fs::write(&path, &data)?;
+                image_file_names.insert(name);
+                image["uri"] = json!(file_name(&path));
+            }
+        }
+    }
+
+    let buffer = match target {
+        Target::Glb => json!({ "byteLength": bin.len() }),
+        Target::Gltf { embed: true } => json!({
+            "byteLength": bin.len(),
+            "uri": super::data_uri("application/octet-stream", &bin),
+        }),
+        Target::Gltf { embed: false } => {
+            let path = super::sibling_path(output, "", "bin");
+            fs::write(&path, &bin)?;
+            json!({ "byteLength": bin.len(), "uri": file_name(&path) })
+        }
+    };
+
+    let root = json
+        .as_object_mut()
+        .ok_or("the document root is not an object")?;
+    if bin.is_empty() {
+        root.remove("buffers");
+    } else {
+        root.insert(String::from("buffers"), json!([buffer]));
+    }
+    if !new_views.is_empty() {
+        root.insert(String::from("bufferViews"), Value::Array(new_views));
+    }
+    if !images.is_empty() {
+        root.insert(String::from("images"), Value::Array(images));
+    }
+
+    match target {
+        Target::Glb => super::write_glb(output, &json, bin),
+        Target::Gltf { .. } => super::write_gltf(output, &json),
+    }
+}
+
+fn take_array(json: &mut Value, key: &str) -> Vec<Value> {
+    match json.as_object_mut().and_then(|root| root.remove(key)) {
+        Some(Value::Array(array)) => array,
+        _ => Vec::new(),
+    }
+}
+
+/// Calls `f` on the value of every `bufferView` property in the document, including the
+/// ones inside extensions.
+fn visit_buffer_views(json: &mut Value, f: &mut impl FnMut(&mut Value)) {
+    match json {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if key == "bufferView" {
+                    f(value);
+                } else {
+                    visit_buffer_views(value, f);
+                }
+            }
+        }
+        Value::Array(array) => {
+            for value in array {
+                visit_buffer_views(value, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn view_bytes<'a>(buffers: &[&'a [u8]], view: &Value) -> Option<&'a [u8]> {
+    let buffer = buffers.get(as_index(&view["buffer"])?)?;
+    let offset = view.get("byteOffset").map_or(Some(0), as_index)?;
+    let length = as_index(&view["byteLength"])?;
+    buffer.get(offset..offset.checked_add(length)?)
+}
+
+fn align_to_four(bin: &mut Vec<u8>) {
+    while bin.len() % 4 != 0 {
+        bin.push(0);
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,185 @@
+use std::{collections::HashMap, error::Error};
+
+use serde_json::{json, Value};
+
+use super::references::{as_index, references, Kind};
+use crate::document::Document;
+
+/// How the scenes of the merged documents are combined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// Every scene of every document is kept as a scene of its own.
+    Scenes,
+    /// A single scene with one new root node per document, holding the root nodes of that
+    /// document's default scene.
+    RootNodes,
+}
+
+/// The merged JSON and the data of its buffers.
+pub type Merged<'a> = (Value, Vec<&'a [u8]>);
+
+/// Combines several documents into one, given the raw JSON of each along with its data.
+///
+/// Every index is shifted past the objects of the documents before it, and buffers are
+/// appended, so the result is meant to be handed to `convert` together with the returned
+/// buffer data. External image URIs are embedded as data URIs, since the documents may
+/// not share a directory.
+///
+/// Lights of KHR_lights_punctual are merged like any other object. Other root-level
+/// extension data is taken from the first document that has it.
+pub fn merge(
+    inputs: Vec<(Value, &Document)>,
+    layout: Layout,
+) -> Result<Merged<'_>, Box<dyn Error>> {
+    let mut merged = json!({});
+    let mut buffers = Vec::new();
+    // The default scene of every document, as an index into the merged scenes.
+    let mut default_scenes = Vec::with_capacity(inputs.len());
+    let mut names = Vec::with_capacity(inputs.len());
+
+    for (mut json, document) in inputs {
+        names.push(
+            document
+                .path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        );
+        embed_images(&mut json, document)?;
+
+        let offsets: HashMap<Kind, usize> = Kind::ALL
+            .iter()
+            .map(|&kind| (kind, kind.objects(&merged).map_or(0, Vec::len)))
+            .collect();
+
+        let scenes = Kind::Scene.objects(&json).map_or(0, Vec::len);
+        let default_scene =
+            json.get("scene")
+                .and_then(as_index)
+                .or(if scenes > 0 { Some(0) } else { None });
+        default_scenes.push(default_scene.map(|scene| scene + offsets[&Kind::Scene]));
+
+        for &kind in Kind::ALL {
+            let objects = match kind.objects_mut(&mut json) {
+                Some(objects) => std::mem::take(objects),
+                None => continue,
+            };
+            let target = objects_entry(&mut merged, kind);
+            for mut object in objects {
+                references(kind, &mut object, &mut |kind, value| {
+                    if let Some(index) = as_index(value) {
+                        *value = json!(index + offsets[&kind]);
+                    }
+                });
+                target.push(object);
+            }
+        }
+        buffers.extend(document.buffers()?);
+
+        merge_root(&mut merged, json);
+    }
+
+    match layout {
+        Layout::Scenes => {
+            if let Some(scene) = default_scenes.into_iter().flatten().next() {
+                merged["scene"] = json!(scene);
+            }
+        }
+        Layout::RootNodes => {
+            let mut roots = Vec::new();
+            for (scene, name) in default_scenes.into_iter().zip(names) {
+                let scene = match scene {
+                    Some(scene) => scene,
+                    None => continue,
+                };
+                let mut node = json!({ "name": name });
+                if let Some(children) = merged["scenes"][scene].get("nodes") {
+                    node["children"] = children.clone();
+                }
+                let nodes = objects_entry(&mut merged, Kind::Node);
+                nodes.push(node);
+                roots.push(nodes.len() - 1);
+            }
+            merged["scenes"] = json!([{ "nodes": roots }]);
+            merged["scene"] = json!(0);
+        }
+    }
+
+    Ok((merged, buffers))
+}
+
+/// Replaces external image URIs by data URIs.
+fn embed_images(json: &mut Value, document: &Document) -> Result<(), Box<dyn Error>> {
+    let images = match Kind::Image.objects_mut(json) {
+        Some(images) => images,
+        None => return Ok(()),
+    };
+    for image in images {
+        let uri = match image.get("uri").and_then(Value::as_str) {
+            Some(uri) if !uri.starts_with("data:") => uri,
+            _ => continue,
+        };
+        let data = super::read_uri(document.base_dir(), uri)?;
+        let mime_type = image
+            .get("mimeType")
+            .and_then(Value::as_str)
+            .or_else(|| super::image_mime_type(&data))
+            .unwrap_or("application/octet-stream");
+        image["uri"] = json!(super::data_uri(mime_type, &data));
+    }
+    Ok(())
+}
+
+/// The objects of `kind` in `root`, creating the array if there is none yet.
+fn objects_entry(root: &mut Value, kind: Kind) -> &mut Vec<Value> {
+    match kind {
+        Kind::Light => array_entry(&mut root["extensions"]["KHR_lights_punctual"]["lights"]),
+        _ => array_entry(&mut root[kind.key()]),
+    }
+}
+
+fn array_entry(slot: &mut Value) -> &mut Vec<Value> {
+    if !slot.is_array() {
+        *slot = json!([]);
+    }
+    match slot {
+        Value::Array(array) => array,
+        _ => unreachable!(),
+    }
+}
+
+/// Merges the root properties of `json` that are not object arrays into `merged`.
+fn merge_root(merged: &mut Value, json: Value) {
+    let root = match json {
+        Value::Object(root) => root,
+        _ => return,
+    };
+    for (key, value) in root {
+        match key.as_str() {
+            "extensionsUsed" | "extensionsRequired" => {
+                let names = array_entry(&mut merged[key.as_str()]);
+                for name in value.as_array().into_iter().flatten() {
+                    if !names.contains(name) {
+                        names.push(name.clone());
+                    }
+                }
+            }
+            "extensions" => {
+                for (name, value) in value.as_object().into_iter().flatten() {
+                    // KHR_lights_punctual is already there, with the lights merged above.
+                    let slot = &mut merged["extensions"][name.as_str()];
+                    if slot.is_null() {
+                        *slot = value.clone();
+                    }
+                }
+            }
+            // The default scene is set once all documents are merged.
+            "scene" => {}
+            _ => {
+                if let Some(merged) = merged.as_object_mut() {
+                    merged.entry(key).or_insert(value);
+                }
+            }
+        }
+    }
+}
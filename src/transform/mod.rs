@@ -0,0 +1,131 @@
+use std::{
+    borrow::Cow,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+
+use crate::archive;
+
+pub mod convert;
+pub mod merge;
+pub mod prune;
+pub mod references;
+
+/// Reads the JSON part of a .gltf or .glb file without interpreting it.
+///
+/// Working on the raw JSON instead of `gltf::json::Root` keeps extensions and extras
+/// that the `gltf` crate doesn't know about intact.
+pub fn read_json(path: &Path) -> Result<Value, Box<dyn Error>> {
+    let bytes = archive::read(path)?;
+    if bytes.starts_with(b"glTF") {
+        let glb = gltf::Glb::from_slice(&bytes)?;
+        Ok(serde_json::from_slice(&glb.json)?)
+    } else {
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+}
+
+/// Writes a document as binary glTF, with `bin` as its embedded buffer.
+pub fn write_glb(path: &Path, json: &Value, bin: Vec<u8>) -> Result<(), Box<dyn Error>> {
+    let glb = gltf::Glb {
+        header: gltf::binary::Header {
+            magic: *b"glTF",
+            version: 2,
+            // Computed by `Glb::to_writer`.
+            length: 0,
+        },
+        json: Cow::Owned(serde_json::to_vec(json)?),
+        bin: if bin.is_empty() {
+            None
+        } else {
+            Some(Cow::Owned(bin))
+        },
+    };
+    glb.to_writer(fs::File::create(path)?)?;
+    Ok(())
+}
+
+/// Writes a document as JSON glTF.
+pub fn write_gltf(path: &Path, json: &Value) -> Result<(), Box<dyn Error>> {
+    fs::write(path, serde_json::to_vec_pretty(json)?)?;
+    Ok(())
+}
+
+/// Reads the data behind a buffer or image URI, resolving relative paths against `base`.
+pub fn read_uri(base: &Path, uri: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, data) = data
+            .split_once(";base64,")
+            .ok_or_else(|| format!("unsupported data URI: {}", truncated(uri)))?;
+        Ok(base64::decode(data)?)
+    } else if uri.contains(':') && !uri.starts_with("file:") {
+        Err(format!("unsupported URI scheme: {}", uri).into())
+    } else {
+        let path = uri
+            .trim_start_matches("file://")
+            .trim_start_matches("file:");
+        Ok(archive::read(&base.join(path))?)
+    }
+}
+
+/// The media type in a `data:` URI, e.g. `image/png`.
+pub fn data_uri_mime_type(uri: &str) -> Option<&str> {
+    uri.strip_prefix("data:")?
+        .split(&[';', ','][..])
+        .next()
+        .filter(|mime_type| !mime_type.is_empty())
+}
+
+pub fn data_uri(mime_type: &str, data: &[u8]) -> String {
+    format!("data:{};base64,{}", mime_type, base64::encode(data))
+}
+
+/// Guesses the media type of an encoded image from its magic bytes.
+pub fn image_mime_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else {
+        None
+    }
+}
+
+pub fn image_extension(mime_type: &str) -> &'static str {
+    match mime_type {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        _ => "bin",
+    }
+}
+
+/// Turns an object name into something that is safe to use in a file name.
+pub fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// `dir/stem_suffix.extension`, next to `path`.
+pub fn sibling_path(path: &Path, suffix: &str, extension: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}{}.{}", stem, suffix, extension))
+}
+
+fn truncated(uri: &str) -> &str {
+    uri.char_indices()
+        .nth(64)
+        .map_or(uri, |(index, _)| &uri[..index])
+}
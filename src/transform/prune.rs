@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde_json::{json, Value};
+
+use super::references::{array_mut, as_index, references, Kind};
+
+/// Removes every object that cannot be reached from a scene, and renumbers the rest.
+///
+/// Nodes are kept if they are in a scene or are joints of a skin used in a scene. Animation
+/// channels targeting removed nodes are dropped, along with animations left without any
+/// channels. Buffers are left alone; `convert` only copies the buffer views that remain.
+pub fn prune(json: &mut Value) {
+    let mut reachable: BTreeMap<Kind, BTreeSet<usize>> = BTreeMap::new();
+    let scenes = json["scenes"].as_array().map_or(0, Vec::len);
+    mark(
+        json,
+        &mut reachable,
+        (0..scenes).map(|i| (Kind::Scene, i)).collect(),
+    );
+
+    let nodes = reachable.get(&Kind::Node).cloned().unwrap_or_default();
+    prune_animations(json, &nodes);
+    let animations = json["animations"].as_array().map_or(0, Vec::len);
+    mark(
+        json,
+        &mut reachable,
+        (0..animations).map(|i| (Kind::Animation, i)).collect(),
+    );
+
+    let mut new_indices: HashMap<Kind, HashMap<usize, usize>> = HashMap::new();
+    for &kind in Kind::PRUNED {
+        let kept = reachable.get(&kind).cloned().unwrap_or_default();
+        let indices = new_indices.entry(kind).or_default();
+        if let Some(array) = kind.objects_mut(json) {
+            let objects = std::mem::take(array);
+            *array = objects
+                .into_iter()
+                .enumerate()
+                .filter(|(index, _)| kept.contains(index))
+                .map(|(index, object)| {
+                    indices.insert(index, indices.len());
+                    object
+                })
+                .collect();
+        }
+    }
+
+    for &kind in Kind::PRUNED.iter().chain(&[Kind::Scene, Kind::Animation]) {
+        if let Some(array) = kind.objects_mut(json) {
+            for object in array {
+                references(kind, object, &mut |kind, value| {
+                    let new_index = as_index(value)
+                        .and_then(|index| new_indices.get(&kind)?.get(&index).copied());
+                    if let Some(new_index) = new_index {
+                        *value = json!(new_index);
+                    }
+                });
+            }
+        }
+    }
+
+    if let Some(root) = json.as_object_mut() {
+        // The schema does not allow empty arrays.
+        let empty: Vec<String> = root
+            .iter()
+            .filter(|(_, value)| value.as_array().is_some_and(Vec::is_empty))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in empty {
+            root.remove(&key);
+        }
+    }
+}
+
+/// Adds everything reachable from `pending` to `reachable`.
+fn mark(
+    json: &mut Value,
+    reachable: &mut BTreeMap<Kind, BTreeSet<usize>>,
+    mut pending: Vec<(Kind, usize)>,
+) {
+    while let Some((kind, index)) = pending.pop() {
+        if !reachable.entry(kind).or_default().insert(index) {
+            continue;
+        }
+        if let Some(object) = kind
+            .objects_mut(json)
+            .and_then(|objects| objects.get_mut(index))
+        {
+            references(kind, object, &mut |kind, value| {
+                if let Some(index) = as_index(value) {
+                    pending.push((kind, index));
+                }
+            });
+        }
+    }
+}
+
+/// Drops channels that target removed nodes, the samplers only they used, and animations
+/// without channels.
+fn prune_animations(json: &mut Value, nodes: &BTreeSet<usize>) {
+    let animations = match json.get_mut("animations").and_then(Value::as_array_mut) {
+        Some(animations) => animations,
+        None => return,
+    };
+
+    for animation in animations.iter_mut() {
+        if let Some(channels) = animation.get_mut("channels").and_then(Value::as_array_mut) {
+            // Channels without a node target may be animating something else entirely.
+            channels.retain(|channel| {
+                as_index(&channel["target"]["node"]).map_or(true, |node| nodes.contains(&node))
+            });
+        }
+
+        let used: BTreeSet<usize> = animation["channels"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|channel| as_index(&channel["sampler"]))
+            .collect();
+        let new_indices: HashMap<usize, usize> = used
+            .iter()
+            .enumerate()
+            .map(|(new_index, &index)| (index, new_index))
+            .collect();
+
+        if let Some(samplers) = animation.get_mut("samplers").and_then(Value::as_array_mut) {
+            let old = std::mem::take(samplers);
+            *samplers = old
+                .into_iter()
+                .enumerate()
+                .filter(|(index, _)| used.contains(index))
+                .map(|(_, sampler)| sampler)
+                .collect();
+        }
+        for channel in array_mut(animation, "channels") {
+            if let Some(&new_index) =
+                as_index(&channel["sampler"]).and_then(|index| new_indices.get(&index))
+            {
+                channel["sampler"] = json!(new_index);
+            }
+        }
+    }
+
+    animations.retain(|animation| {
+        animation["channels"]
+            .as_array()
+            .is_some_and(|channels| !channels.is_empty())
+    });
+}
@@ -0,0 +1,257 @@
+//! The places where glTF objects refer to each other by index.
+
+use serde_json::Value;
+
+/// The kinds of top-level objects that refer to each other by index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Kind {
+    Scene,
+    Node,
+    Mesh,
+    Skin,
+    Camera,
+    Accessor,
+    BufferView,
+    Material,
+    Texture,
+    Image,
+    Sampler,
+    Animation,
+    Buffer,
+    Light,
+}
+
+impl Kind {
+    pub const ALL: &'static [Kind] = &[
+        Kind::Scene,
+        Kind::Node,
+        Kind::Mesh,
+        Kind::Skin,
+        Kind::Camera,
+        Kind::Accessor,
+        Kind::BufferView,
+        Kind::Material,
+        Kind::Texture,
+        Kind::Image,
+        Kind::Sampler,
+        Kind::Animation,
+        Kind::Buffer,
+        Kind::Light,
+    ];
+
+    /// The kinds removed by `prune` when nothing refers to them.
+    pub const PRUNED: &'static [Kind] = &[
+        Kind::Node,
+        Kind::Mesh,
+        Kind::Skin,
+        Kind::Camera,
+        Kind::Accessor,
+        Kind::BufferView,
+        Kind::Material,
+        Kind::Texture,
+        Kind::Image,
+        Kind::Sampler,
+    ];
+
+    /// The objects of this kind in a document.
+    pub fn objects(self, root: &Value) -> Option<&Vec<Value>> {
+        match self {
+            Kind::Light => root
+                .get("extensions")?
+                .get("KHR_lights_punctual")?
+                .get("lights"),
+            _ => root.get(self.key()),
+        }?
+        .as_array()
+    }
+
+    pub fn objects_mut(self, root: &mut Value) -> Option<&mut Vec<Value>> {
+        match self {
+            Kind::Light => root
+                .get_mut("extensions")?
+                .get_mut("KHR_lights_punctual")?
+                .get_mut("lights"),
+            _ => root.get_mut(self.key()),
+        }?
+        .as_array_mut()
+    }
+
+    /// The property of the document root holding the objects of this kind, except for
+    /// lights, which live in the KHR_lights_punctual extension.
+    pub fn key(self) -> &'static str {
+        match self {
+            Kind::Scene => "scenes",
+            Kind::Node => "nodes",
+            Kind::Mesh => "meshes",
+            Kind::Skin => "skins",
+            Kind::Camera => "cameras",
+            Kind::Accessor => "accessors",
+            Kind::BufferView => "bufferViews",
+            Kind::Material => "materials",
+            Kind::Texture => "textures",
+            Kind::Image => "images",
+            Kind::Sampler => "samplers",
+            Kind::Animation => "animations",
+            Kind::Buffer => "buffers",
+            Kind::Light => "lights",
+        }
+    }
+}
+
+/// Calls `f` with every index in `object` that refers to another top-level object.
+pub fn references(kind: Kind, object: &mut Value, f: &mut impl FnMut(Kind, &mut Value)) {
+    match kind {
+        Kind::Scene => each(object, "nodes", Kind::Node, f),
+        Kind::Node => {
+            each(object, "children", Kind::Node, f);
+            field(object, "mesh", Kind::Mesh, f);
+            field(object, "skin", Kind::Skin, f);
+            field(object, "camera", Kind::Camera, f);
+            if let Some(light) = object
+                .get_mut("extensions")
+                .and_then(|extensions| extensions.get_mut("KHR_lights_punctual"))
+            {
+                field(light, "light", Kind::Light, f);
+            }
+        }
+        Kind::Mesh => {
+            for primitive in array_mut(object, "primitives") {
+                for (_, accessor) in object_mut(primitive, "attributes") {
+                    f(Kind::Accessor, accessor);
+                }
+                for target in array_mut(primitive, "targets") {
+                    if let Some(target) = target.as_object_mut() {
+                        for accessor in target.values_mut() {
+                            f(Kind::Accessor, accessor);
+                        }
+                    }
+                }
+                field(primitive, "indices", Kind::Accessor, f);
+                field(primitive, "material", Kind::Material, f);
+                // Such as the material variants of KHR_materials_variants, or the compressed
+                // data of KHR_draco_mesh_compression.
+                if let Some(extensions) = primitive.get_mut("extensions") {
+                    visit_keys(extensions, "material", Kind::Material, f);
+                    visit_keys(extensions, "bufferView", Kind::BufferView, f);
+                }
+            }
+        }
+        Kind::Skin => {
+            field(object, "inverseBindMatrices", Kind::Accessor, f);
+            field(object, "skeleton", Kind::Node, f);
+            each(object, "joints", Kind::Node, f);
+        }
+        Kind::BufferView => field(object, "buffer", Kind::Buffer, f),
+        Kind::Camera | Kind::Sampler | Kind::Buffer | Kind::Light => {}
+        Kind::Accessor => {
+            field(object, "bufferView", Kind::BufferView, f);
+            if let Some(sparse) = object.get_mut("sparse") {
+                for key in &["indices", "values"] {
+                    if let Some(data) = sparse.get_mut(*key) {
+                        field(data, "bufferView", Kind::BufferView, f);
+                    }
+                }
+            }
+        }
+        Kind::Material => texture_infos(object, f),
+        Kind::Texture => {
+            field(object, "sampler", Kind::Sampler, f);
+            field(object, "source", Kind::Image, f);
+            // Such as the alternative sources of KHR_texture_basisu or EXT_texture_webp.
+            if let Some(extensions) = object.get_mut("extensions") {
+                visit_keys(extensions, "source", Kind::Image, f);
+            }
+        }
+        Kind::Image => field(object, "bufferView", Kind::BufferView, f),
+        Kind::Animation => {
+            for channel in array_mut(object, "channels") {
+                if let Some(node) = channel.get_mut("target").and_then(|t| t.get_mut("node")) {
+                    f(Kind::Node, node);
+                }
+            }
+            for sampler in array_mut(object, "samplers") {
+                field(sampler, "input", Kind::Accessor, f);
+                field(sampler, "output", Kind::Accessor, f);
+            }
+        }
+    }
+
+    // Extensions may store data in buffer views of their own.
+    if let Some(extensions) = object.get_mut("extensions") {
+        visit_keys(extensions, "bufferView", Kind::BufferView, f);
+    }
+}
+
+fn field(object: &mut Value, key: &str, target: Kind, f: &mut impl FnMut(Kind, &mut Value)) {
+    if let Some(value) = object.get_mut(key) {
+        f(target, value);
+    }
+}
+
+fn each(object: &mut Value, key: &str, target: Kind, f: &mut impl FnMut(Kind, &mut Value)) {
+    for value in array_mut(object, key) {
+        f(target, value);
+    }
+}
+
+/// Calls `f` with the `index` of every texture info, that is every object under a key
+/// ending in `Texture`, in core material properties and extensions alike.
+fn texture_infos(value: &mut Value, f: &mut impl FnMut(Kind, &mut Value)) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if key.ends_with("Texture") {
+                    if let Some(index) = value.get_mut("index") {
+                        f(Kind::Texture, index);
+                    }
+                } else {
+                    texture_infos(value, f);
+                }
+            }
+        }
+        Value::Array(array) => array.iter_mut().for_each(|value| texture_infos(value, f)),
+        _ => {}
+    }
+}
+
+/// Calls `f` with the value of every property named `key` below `value`.
+fn visit_keys(value: &mut Value, key: &str, target: Kind, f: &mut impl FnMut(Kind, &mut Value)) {
+    match value {
+        Value::Object(object) => {
+            for (name, value) in object.iter_mut() {
+                if name == key {
+                    f(target, value);
+                } else {
+                    visit_keys(value, key, target, f);
+                }
+            }
+        }
+        Value::Array(array) => array
+            .iter_mut()
+            .for_each(|value| visit_keys(value, key, target, f)),
+        _ => {}
+    }
+}
+
+pub fn array_mut<'a>(object: &'a mut Value, key: &str) -> impl Iterator<Item = &'a mut Value> {
+    object
+        .get_mut(key)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+}
+
+fn object_mut<'a>(
+    object: &'a mut Value,
+    key: &str,
+) -> impl Iterator<Item = (&'a String, &'a mut Value)> {
+    object
+        .get_mut(key)
+        .and_then(Value::as_object_mut)
+        .into_iter()
+        .flatten()
+}
+
+pub fn as_index(value: &Value) -> Option<usize> {
+    value.as_u64().map(|index| index as usize)
+}
@@ -0,0 +1,52 @@
+//! Noticing when files change on disk, by polling their modification times and sizes.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::archive;
+
+/// How often watched files are checked.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug)]
+pub struct Watcher {
+    /// Each file with the modification time and size last seen, `None` while the file
+    /// can't be read.
+    files: Vec<(PathBuf, Option<(SystemTime, u64)>)>,
+}
+
+impl Watcher {
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            files: paths
+                .into_iter()
+                .map(|path| {
+                    let stamp = stamp(&path);
+                    (path, stamp)
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether any of the files changed since the last call. A file that was deleted
+    /// doesn't count as changed until it comes back, since editors often replace files
+    /// that way.
+    pub fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (path, last_stamp) in &mut self.files {
+            let stamp = stamp(path);
+            changed |= stamp.is_some() && stamp != *last_stamp;
+            *last_stamp = stamp;
+        }
+        changed
+    }
+}
+
+/// The stamp of the file on disk, which is the whole archive for a file inside one.
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(archive::containing_file(path)).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}